@@ -0,0 +1,167 @@
+use aivd_solver::strategy::{self, Dictionary, Puzzle};
+use aivd_solver::{encoder, old2solver, oldsolver, solver};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::time::Duration;
+
+// Dictionary sizes, and (number of words, word length) for the synthetic puzzles
+const DICTIONARY_SIZES: [usize; 3] = [1_000, 10_000, 50_000];
+const PUZZLE_SHAPES: [(usize, usize); 3] = [(2, 4), (3, 5), (4, 6)];
+
+// Small deterministic random number generator, so every run benchmarks the same puzzles
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self, max: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % max as u64) as usize
+    }
+}
+
+// A wordlist of random lowercase words with 2 to 9 letters
+fn synthetic_wordlist(size: usize) -> String {
+    let mut random = Random(size as u64);
+    let mut words: Vec<String> = (0..size)
+        .map(|_| {
+            let length = 2 + random.next(8);
+            (0..length)
+                .map(|_| (b'a' + random.next(26) as u8) as char)
+                .collect()
+        })
+        .collect();
+    words.sort_unstable();
+    words.dedup();
+    words.join("\n")
+}
+
+// Encode a sentence of random words with the given length from the wordlist
+fn synthetic_puzzle(wordlist: &str, word_count: usize, word_length: usize) -> String {
+    let candidates: Vec<&str> = wordlist
+        .lines()
+        .filter(|word| word.len() == word_length)
+        .collect();
+    let mut random = Random((word_count * 100 + word_length) as u64);
+    let sentence: Vec<&str> = (0..word_count)
+        .map(|_| candidates[random.next(candidates.len())])
+        .collect();
+    encoder::encode(&sentence.join(" ")).ciphertext
+}
+
+fn trie_construction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("trie_construction");
+    for size in DICTIONARY_SIZES {
+        let wordlist = synthetic_wordlist(size);
+        group.bench_with_input(
+            BenchmarkId::new("solver", size),
+            &wordlist,
+            |b, wordlist| {
+                b.iter(|| solver::construct_tree(solver::filter_wordlist(black_box(wordlist))))
+            },
+        );
+    }
+    group.finish();
+}
+
+fn candidate_filtering(c: &mut Criterion) {
+    let mut group = c.benchmark_group("candidate_filtering");
+    for size in DICTIONARY_SIZES {
+        let wordlist = synthetic_wordlist(size);
+        for (word_count, word_length) in PUZZLE_SHAPES {
+            let input = synthetic_puzzle(&wordlist, word_count, word_length);
+            let input_numbers = solver::parse_input(&input);
+            let number_letter_counts = solver::get_number_of_characters_per_number_count(&input);
+            let parameter = format!("{}x{}/{}", word_count, word_length, size);
+
+            group.bench_function(BenchmarkId::new("solver", &parameter), |b| {
+                let filtered = solver::filter_wordlist(&wordlist);
+                let solution = solver::empty_solution(&input_numbers);
+                let mut counts = solver::LetterCounts::new(&input_numbers, &number_letter_counts);
+                b.iter(|| {
+                    (0..input_numbers.len())
+                        .map(|i| {
+                            solver::count_candidates(
+                                &solution,
+                                &input_numbers,
+                                &mut counts,
+                                &filtered,
+                                i,
+                            )
+                        })
+                        .sum::<usize>()
+                })
+            });
+            group.bench_function(BenchmarkId::new("old2solver", &parameter), |b| {
+                let words: Vec<&str> = wordlist.lines().collect();
+                b.iter(|| old2solver::filter_candidates(&input_numbers, black_box(&words)))
+            });
+            group.bench_function(BenchmarkId::new("oldsolver", &parameter), |b| {
+                let words: Vec<&str> = wordlist.lines().collect();
+                b.iter(|| oldsolver::filter_candidates(&input_numbers, black_box(&words)))
+            });
+        }
+    }
+    group.finish();
+}
+
+// Every registered strategy with a shared dictionary (only solver.rs searches for a full decoding, the older strategies stop after filtering candidates)
+fn full_solve(c: &mut Criterion) {
+    let mut group = c.benchmark_group("full_solve");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(5));
+    for size in DICTIONARY_SIZES {
+        let wordlist = synthetic_wordlist(size);
+        let dictionary = Dictionary::new(&wordlist);
+        for (word_count, word_length) in PUZZLE_SHAPES {
+            let puzzle = Puzzle::parse(&synthetic_puzzle(&wordlist, word_count, word_length));
+            let parameter = format!("{}x{}/{}", word_count, word_length, size);
+            for strategy in strategy::strategies() {
+                group.bench_function(BenchmarkId::new(strategy.name(), &parameter), |b| {
+                    b.iter(|| {
+                        strategy.solve(black_box(&puzzle), &dictionary, &solver::Options::default())
+                    })
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
+// The backtracking solver with the fixed left to right order against filling in the most constrained position first
+fn ordering(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ordering");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(5));
+    let backtracking = strategy::find_strategy("solver").unwrap();
+    for size in DICTIONARY_SIZES {
+        let wordlist = synthetic_wordlist(size);
+        let dictionary = Dictionary::new(&wordlist);
+        for (word_count, word_length) in PUZZLE_SHAPES {
+            let puzzle = Puzzle::parse(&synthetic_puzzle(&wordlist, word_count, word_length));
+            let parameter = format!("{}x{}/{}", word_count, word_length, size);
+            for (name, order) in [
+                ("fixed", solver::Order::Fixed),
+                ("most-constrained", solver::Order::MostConstrained),
+            ] {
+                let options = solver::Options {
+                    order,
+                    ..Default::default()
+                };
+                group.bench_function(BenchmarkId::new(name, &parameter), |b| {
+                    b.iter(|| backtracking.solve(black_box(&puzzle), &dictionary, &options))
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    trie_construction,
+    candidate_filtering,
+    full_solve,
+    ordering
+);
+criterion_main!(benches);
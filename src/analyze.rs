@@ -0,0 +1,172 @@
+use color_eyre::owo_colors::OwoColorize;
+
+use crate::letters::LetterSet;
+use crate::old2solver::{self, Word};
+use crate::solver;
+use crate::strategy::{Dictionary, Puzzle};
+
+// Everything that can be said about a puzzle before searching for a decoding
+pub struct Report {
+    // How often every number appears in the ciphertext
+    pub number_counts: Vec<usize>,
+    // How many different letters every number stands for
+    pub number_letter_counts: Vec<u32>,
+    // Reasons the ciphertext can't have a decoding at all
+    pub inconsistencies: Vec<String>,
+    // Candidate words per cipher word, before and after the possible letters per number are propagated between the words
+    pub candidates_before: Vec<usize>,
+    pub candidates_after: Vec<usize>,
+    // The possible letters per number after propagation
+    pub possible_letters: Vec<LetterSet>,
+    // The number of combinations of candidate words, after propagation
+    pub search_space: f64,
+}
+
+pub fn analyze(input: &str, dictionary: &Dictionary) -> Report {
+    let puzzle = Puzzle::parse(input);
+
+    let mut number_counts: Vec<usize> = vec![0; puzzle.number_letter_counts.len()];
+    for number in puzzle.input_numbers.iter().flatten() {
+        number_counts[*number as usize] += 1;
+    }
+
+    // A number must appear a multiple of itself times, and there are only the letters of the wordlist to go around
+    let mut inconsistencies = Vec::new();
+    for number in solver::oversized_numbers(input) {
+        inconsistencies.push(format!(
+            "{} is larger than the number of numbers, so it is left out",
+            number
+        ));
+    }
+    for (number, count) in number_counts.iter().enumerate() {
+        if *count == 0 {
+            continue;
+        }
+        if number == 0 {
+            inconsistencies.push(format!(
+                "0 appears {} times, but no letter appears 0 times",
+                count
+            ));
+        } else if count % number != 0 {
+            inconsistencies.push(format!(
+                "{} appears {} times, which is not a multiple of {}",
+                number, count, number
+            ));
+        }
+    }
+    let letters: u32 = puzzle.number_letter_counts.iter().sum();
+    let alphabet = dictionary.tree.letters().len();
+    if letters as usize > alphabet {
+        inconsistencies.push(format!(
+            "The numbers need {} different letters, but the wordlist only has {}",
+            letters, alphabet
+        ));
+    }
+
+    // The words from the wordlist that fit every cipher word on their own
+    let empty_solution = solver::empty_solution(&puzzle.input_numbers);
+    let mut counts = solver::LetterCounts::new(&puzzle.input_numbers, &puzzle.number_letter_counts);
+    let mut words: Vec<Word> = (0..puzzle.input_numbers.len())
+        .map(|i| Word {
+            numbers: puzzle.input_numbers[i].clone(),
+            possible_words: solver::explain_candidates(
+                &empty_solution,
+                &puzzle.input_numbers,
+                &mut counts,
+                &dictionary.words,
+                i,
+            )
+            .into_iter()
+            .filter(|(_, conflict)| conflict.is_none())
+            .map(|(word, _)| word.to_string())
+            .collect(),
+        })
+        .collect();
+    let candidates_before: Vec<usize> =
+        words.iter().map(|word| word.possible_words.len()).collect();
+    for (i, candidates) in candidates_before.iter().enumerate() {
+        if *candidates == 0 {
+            inconsistencies.push(format!(
+                "Word {} ({}) has no candidates in the wordlist",
+                i + 1,
+                solver::format_numbers(&puzzle.input_numbers[i])
+            ));
+        }
+    }
+
+    // Only keep the letters that some candidate puts under a number, and the candidates that only use those letters,
+    // until nothing changes anymore
+    let mut possible_letters: Vec<LetterSet> = vec![LetterSet::new(); number_counts.len()];
+    loop {
+        let before: usize = words.iter().map(|word| word.possible_words.len()).sum();
+        old2solver::update_possible_letters(&words, &mut possible_letters);
+        old2solver::further_refine_possible_words(&mut words, &possible_letters);
+        let after: usize = words.iter().map(|word| word.possible_words.len()).sum();
+        if after == before {
+            break;
+        }
+    }
+    let candidates_after: Vec<usize> = words.iter().map(|word| word.possible_words.len()).collect();
+
+    let search_space = candidates_after
+        .iter()
+        .map(|candidates| *candidates as f64)
+        .product();
+
+    Report {
+        number_counts,
+        number_letter_counts: puzzle.number_letter_counts,
+        inconsistencies,
+        candidates_before,
+        candidates_after,
+        possible_letters,
+        search_space,
+    }
+}
+
+pub fn print_report(input: &str, report: &Report) {
+    println!("Ciphertext: {}", input.trim());
+
+    println!("\nNumber  Count  Letters  Possible letters");
+    for (number, count) in report.number_counts.iter().enumerate() {
+        if *count == 0 {
+            continue;
+        }
+        println!(
+            "  {:<4}  {:>5}  {:>7}  {}",
+            number, count, report.number_letter_counts[number], report.possible_letters[number]
+        );
+    }
+    let letters: u32 = report.number_letter_counts.iter().sum();
+    println!(
+        "Letters: {} different, {} in total",
+        letters,
+        report.number_counts.iter().sum::<usize>()
+    );
+
+    println!();
+    if report.inconsistencies.is_empty() {
+        println!("{}", "No inconsistencies".bright_green());
+    } else {
+        println!("{}", "Inconsistencies:".bright_red());
+        for inconsistency in report.inconsistencies.iter() {
+            println!("  {}", inconsistency);
+        }
+    }
+
+    println!("\nWord  Numbers          Candidates  After propagation");
+    for (i, numbers) in solver::parse_input(input).iter().enumerate() {
+        println!(
+            "  {:<3} {:<16} {:>10}  {:>17}",
+            i + 1,
+            solver::format_numbers(numbers),
+            report.candidates_before[i],
+            report.candidates_after[i]
+        );
+    }
+
+    println!(
+        "\nSearch space: {:.3e} combinations of candidate words",
+        report.search_space
+    );
+}
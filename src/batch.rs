@@ -0,0 +1,168 @@
+use color_eyre::owo_colors::OwoColorize;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::solver;
+use crate::strategy::{self, Dictionary, Puzzle, Strategy};
+
+// A puzzle from the batch, named after its file (and line)
+pub struct BatchPuzzle {
+    pub name: String,
+    pub ciphertext: String,
+}
+
+// What a strategy found for one puzzle of the batch, and how long it took
+pub struct BatchResult {
+    pub name: String,
+    pub words: usize,
+    pub solution: Option<String>,
+    pub candidate_counts: Option<Vec<usize>>,
+    // The ranking stopped at strategy::RANKED_SOLUTIONS solutions
+    pub truncated: bool,
+    pub duration: Duration,
+}
+
+// A file has one puzzle per line (empty lines and lines starting with # are skipped),
+// a directory has one puzzle per file, where the whole file is the puzzle
+pub fn load_puzzles(path: &Path) -> io::Result<Vec<BatchPuzzle>> {
+    if path.is_dir() {
+        let mut paths: Vec<_> = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<_>>()?;
+        paths.retain(|path| path.is_file());
+        paths.sort();
+
+        let mut puzzles = Vec::new();
+        for path in paths {
+            let ciphertext = std::fs::read_to_string(&path)?;
+            puzzles.push(BatchPuzzle {
+                name: file_name(&path),
+                ciphertext: ciphertext.trim().to_string(),
+            });
+        }
+        return Ok(puzzles);
+    }
+
+    let name = file_name(path);
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| BatchPuzzle {
+            name: format!("{}:{}", name, i + 1),
+            ciphertext: line.trim().to_string(),
+        })
+        .collect())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+// Solve every puzzle with the same dictionary, so the wordlist tree is only loaded once
+// Without spaces every puzzle is one long cipher word, which can be split into as many words as it has numbers
+pub fn solve_all(
+    puzzles: &[BatchPuzzle],
+    strategy: &dyn Strategy,
+    dictionary: &Dictionary,
+    options: &solver::Options,
+    no_spaces: bool,
+) -> Vec<BatchResult> {
+    puzzles
+        .iter()
+        .map(|puzzle| {
+            let start = Instant::now();
+            let (parsed, options) = if no_spaces {
+                let parsed = Puzzle::parse_without_spaces(&puzzle.ciphertext);
+                let options = solver::Options {
+                    max_components: parsed.input_numbers.iter().map(|word| word.len()).sum(),
+                    ..options.clone()
+                };
+                (parsed, options)
+            } else {
+                (Puzzle::parse(&puzzle.ciphertext), options.clone())
+            };
+            let outcome = strategy.solve(&parsed, dictionary, &options);
+            // Without spaces there is no punctuation to keep, the words are shown the way the numbers were split
+            let solution = match outcome.solution {
+                Some(_) if no_spaces => Some(outcome.words.concat().join(" ")),
+                Some(solution) => Some(solver::render(&puzzle.ciphertext, &solution)),
+                None => None,
+            };
+            BatchResult {
+                name: puzzle.name.clone(),
+                words: parsed.input_numbers.len(),
+                solution,
+                candidate_counts: outcome.candidate_counts,
+                truncated: outcome.truncated,
+                duration: start.elapsed(),
+            }
+        })
+        .collect()
+}
+
+// Print a line per puzzle, and the totals for the whole batch
+pub fn print_summary(results: &[BatchResult]) {
+    let name_width = results
+        .iter()
+        .map(|result| result.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Puzzle".len());
+    println!(
+        "{:<name_width$}  {:>5}  {:>10}  Result",
+        "Puzzle", "Words", "Time (ms)"
+    );
+
+    for result in results.iter() {
+        // The decoding on a single line, so the table stays readable
+        let mut outcome = match (&result.solution, &result.candidate_counts) {
+            (Some(solution), _) => solution
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ")
+                .bright_green()
+                .to_string(),
+            (None, Some(candidate_counts)) => format!("Candidates: {:?}", candidate_counts),
+            (None, None) => "No solution found".bright_red().to_string(),
+        };
+        if result.truncated {
+            outcome.push_str(&" (ranking limit hit)".bright_yellow().to_string());
+        }
+        println!(
+            "{:<name_width$}  {:>5}  {:>10.3}  {}",
+            result.name,
+            result.words,
+            result.duration.as_secs_f64() * 1000.0,
+            outcome
+        );
+    }
+
+    let total: Duration = results.iter().map(|result| result.duration).sum();
+    let solved = results
+        .iter()
+        .filter(|result| result.solution.is_some())
+        .count();
+    println!("Solved: {}/{}", solved, results.len());
+    let truncated = results.iter().filter(|result| result.truncated).count();
+    if truncated > 0 {
+        println!(
+            "{} {} puzzles had more than {} solutions to rank, a better one may have been missed",
+            "Warning:".bright_yellow(),
+            truncated,
+            strategy::RANKED_SOLUTIONS
+        );
+    }
+    println!("Total time: {:?}", total.as_micros());
+    if let Some(slowest) = results.iter().max_by_key(|result| result.duration) {
+        println!(
+            "Average time: {:?}, slowest: {} ({:?})",
+            (total / results.len() as u32).as_micros(),
+            slowest.name,
+            slowest.duration.as_micros()
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::letters;
+use crate::solver::{self, Node, Tree};
+use crate::strategy::Dictionary;
+
+// Bump the version whenever the file layout changes, so old cache files are rebuilt
+const MAGIC: &[u8; 8] = b"AIVDTRIE";
+const VERSION: u32 = 3;
+
+// Describes how solver::filter_wordlist normalizes the wordlist, change it whenever that does
+const NORMALIZATION: &str = "non-empty lines, letters a-z and à-ÿ in either case only, lowercased";
+
+// Header: magic, version, key, number of words, number of nodes, length of the signatures
+// Every part of the file starts at a multiple of 4 bytes, so the nodes and signature starts can be used in place
+const HEADER_SIZE: usize = 8 + 4 + 8 + 4 + 4 + 4;
+const WORD_SIZE: usize = 12;
+const NODE_SIZE: usize = std::mem::size_of::<Node>();
+
+// One of the lists of the tree, either built in memory or used straight from a mapped cache file without copying it
+pub struct Table<T> {
+    storage: Storage<T>,
+}
+
+enum Storage<T> {
+    Owned(Vec<T>),
+    Mapped {
+        map: Arc<Mmap>,
+        offset: usize,
+        length: usize,
+    },
+}
+
+impl<T> From<Vec<T>> for Table<T> {
+    fn from(items: Vec<T>) -> Table<T> {
+        Table {
+            storage: Storage::Owned(items),
+        }
+    }
+}
+
+impl<T> Table<T> {
+    // Safety: the bytes from the offset must be aligned for T and hold `length` valid values of T
+    unsafe fn mapped(map: &Arc<Mmap>, offset: usize, length: usize) -> Table<T> {
+        debug_assert!(offset + length * std::mem::size_of::<T>() <= map.len());
+        debug_assert!(map.as_ptr().add(offset).cast::<T>().is_aligned());
+        Table {
+            storage: Storage::Mapped {
+                map: Arc::clone(map),
+                offset,
+                length,
+            },
+        }
+    }
+}
+
+impl<T> Deref for Table<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match &self.storage {
+            Storage::Owned(items) => items,
+            // Safety: checked when the table was made, and the map lives as long as the table
+            Storage::Mapped {
+                map,
+                offset,
+                length,
+            } => unsafe { std::slice::from_raw_parts(map.as_ptr().add(*offset).cast(), *length) },
+        }
+    }
+}
+
+// FNV-1a, a simple hash that stays the same between runs and Rust versions
+fn hash(bytes: &[u8], mut hash: u64) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+// The cache key depends on the contents of the wordlist and how it is normalized
+pub fn cache_key(wordlist: &str) -> u64 {
+    hash(
+        wordlist.as_bytes(),
+        hash(NORMALIZATION.as_bytes(), 0xcbf29ce484222325),
+    )
+}
+
+pub fn cache_path(cache_directory: &Path, wordlist: &str) -> PathBuf {
+    cache_directory.join(format!("{:016x}.trie", cache_key(wordlist)))
+}
+
+// Load the dictionary from the cache if there is a valid cache file for this wordlist, otherwise build it and write the cache
+pub fn load_or_build<'a>(wordlist: &'a str, cache_directory: &Path) -> Dictionary<'a> {
+    let path = cache_path(cache_directory, wordlist);
+    if let Some(dictionary) = load(&path, wordlist) {
+        return dictionary;
+    }
+
+    let dictionary = Dictionary::new(wordlist);
+    // The cache is only there to speed things up, so failing to write it is not an error
+    if let Err(error) = save(&path, wordlist, &dictionary) {
+        println!(
+            "Could not write the wordlist cache to {:?}: {}",
+            path, error
+        );
+    }
+    dictionary
+}
+
+fn save(path: &Path, wordlist: &str, dictionary: &Dictionary) -> io::Result<()> {
+    let nodes = &dictionary.tree.nodes;
+    let signatures = &dictionary.tree.signatures;
+    let mut bytes: Vec<u8> = Vec::with_capacity(
+        HEADER_SIZE
+            + dictionary.words.len() * WORD_SIZE
+            + nodes.len() * NODE_SIZE
+            + (nodes.len() + 1) * 4
+            + signatures.len() * 2,
+    );
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&cache_key(wordlist).to_le_bytes());
+    bytes.extend_from_slice(&(dictionary.words.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(nodes.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(signatures.len() as u32).to_le_bytes());
+
+    // The words are stored as (offset, length) in the wordlist, which is needed anyway to check the key,
+    // and whether the line is lowercase already, so only the other words are lowercased again when they are loaded
+    for (line, word) in solver::wordlist_lines(wordlist).zip(dictionary.words.iter()) {
+        let offset = line.as_ptr() as usize - wordlist.as_ptr() as usize;
+        bytes.extend_from_slice(&(offset as u32).to_le_bytes());
+        bytes.extend_from_slice(&(line.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(matches!(word, Cow::Borrowed(_)) as u32).to_le_bytes());
+    }
+    // The tree is already a flat list of nodes, so it can be written as is, in the layout of Node
+    // (character, first child, number of children, is_word and 3 bytes of padding)
+    for node in nodes.iter() {
+        bytes.extend_from_slice(&(node.character as u32).to_le_bytes());
+        bytes.extend_from_slice(&node.first_child.to_le_bytes());
+        bytes.extend_from_slice(&node.child_count.to_le_bytes());
+        bytes.extend_from_slice(&[node.is_word as u8, 0, 0, 0]);
+    }
+    // The signatures of the words, and where the one of every node starts (and the end of the last one)
+    for start in dictionary.tree.signature_starts.iter() {
+        bytes.extend_from_slice(&start.to_le_bytes());
+    }
+    for entry in signatures.iter() {
+        bytes.extend_from_slice(entry);
+    }
+
+    // Write to a temporary file first, so a half written cache file is never read
+    std::fs::create_dir_all(path.parent().unwrap())?;
+    let temporary_path = path.with_extension("tmp");
+    File::create(&temporary_path)?.write_all(&bytes)?;
+    std::fs::rename(temporary_path, path)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        bytes.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+// Map the cache file, returns None if it doesn't exist or doesn't belong to this wordlist
+// The tree is checked once and then used straight from the map, nothing but the word list is copied
+fn load<'a>(path: &Path, wordlist: &'a str) -> Option<Dictionary<'a>> {
+    // The numbers in the file are little endian, elsewhere the cache is simply rebuilt every time
+    if cfg!(target_endian = "big") {
+        return None;
+    }
+    let file = File::open(path).ok()?;
+    // Safety: the file is only read, and cache files are replaced by renaming a new file over them, never written in place
+    let bytes = Arc::new(unsafe { Mmap::map(&file) }.ok()?);
+
+    if bytes.get(0..8)? != MAGIC
+        || read_u32(&bytes, 8)? != VERSION
+        || read_u64(&bytes, 12)? != cache_key(wordlist)
+    {
+        return None;
+    }
+    let word_count = read_u32(&bytes, 20)? as usize;
+    let node_count = read_u32(&bytes, 24)? as usize;
+    let signature_length = read_u32(&bytes, 28)? as usize;
+    let nodes_start = HEADER_SIZE + word_count * WORD_SIZE;
+    let starts_start = nodes_start + node_count * NODE_SIZE;
+    let signatures_start = starts_start + (node_count + 1) * 4;
+    if bytes.len() != signatures_start + signature_length * 2 || node_count == 0 {
+        return None;
+    }
+
+    let mut words = Vec::with_capacity(word_count);
+    for i in 0..word_count {
+        let record = HEADER_SIZE + i * WORD_SIZE;
+        let offset = read_u32(&bytes, record)? as usize;
+        let length = read_u32(&bytes, record + 4)? as usize;
+        let word = wordlist.get(offset..offset + length)?;
+        words.push(if read_u32(&bytes, record + 8)? != 0 {
+            Cow::Borrowed(word)
+        } else {
+            Cow::Owned(word.to_lowercase())
+        });
+    }
+
+    for (index, record) in bytes[nodes_start..starts_start]
+        .chunks_exact(NODE_SIZE)
+        .enumerate()
+    {
+        // Every node must be a valid Node before the nodes can be used in place
+        char::from_u32(read_u32(record, 0)?)?;
+        if record[12] > 1 {
+            return None;
+        }
+
+        // Children always come after their parent, anything else means the file is broken
+        let first_child = read_u32(record, 4)? as usize;
+        let child_count = read_u32(record, 8)? as usize;
+        if child_count > 0 && (first_child <= index || first_child + child_count > node_count) {
+            return None;
+        }
+    }
+
+    // Every signature must end after it starts, and within the signatures
+    let mut last_start = 0;
+    for record in bytes[starts_start..signatures_start].chunks_exact(4) {
+        let start = read_u32(record, 0)?;
+        if start < last_start || start as usize > signature_length {
+            return None;
+        }
+        last_start = start;
+    }
+    if bytes[signatures_start..]
+        .chunks_exact(2)
+        .any(|entry| entry[0] as usize >= letters::CAPACITY)
+    {
+        return None;
+    }
+
+    // Safety: the map is page aligned and every part starts at a multiple of 4, and all values were checked above
+    let tree = unsafe {
+        Tree::from_nodes(
+            Table::mapped(&bytes, nodes_start, node_count),
+            Table::mapped(&bytes, starts_start, node_count + 1),
+            Table::mapped(&bytes, signatures_start, signature_length),
+        )
+    };
+    Some(Dictionary {
+        words,
+        tree,
+        sources: Vec::new(),
+        word_sources: HashMap::new(),
+    })
+}
@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::letters;
+use crate::solver;
+use crate::strategy::{Dictionary, Puzzle};
+
+// Clauses over numbered variables, literals are DIMACS style (3 means variable 3 is true, -3 means it is false)
+#[derive(Default)]
+pub struct Cnf {
+    pub variables: usize,
+    pub clauses: Vec<Vec<i32>>,
+    // What some of the variables mean, written as comments in the DIMACS file
+    pub names: Vec<(i32, String)>,
+}
+
+impl Cnf {
+    pub fn new_variable(&mut self) -> i32 {
+        self.variables += 1;
+        self.variables as i32
+    }
+
+    pub fn add_clause(&mut self, clause: Vec<i32>) {
+        self.clauses.push(clause);
+    }
+
+    // At most k of the literals are true (sequential counter: counter[i][j] means more than j of the first i+1 are true)
+    pub fn at_most(&mut self, literals: &[i32], k: usize) {
+        if k >= literals.len() {
+            return;
+        }
+        if k == 0 {
+            for literal in literals.iter() {
+                self.add_clause(vec![-literal]);
+            }
+            return;
+        }
+
+        let counters: Vec<Vec<i32>> = (0..literals.len() - 1)
+            .map(|_| (0..k).map(|_| self.new_variable()).collect())
+            .collect();
+        self.add_clause(vec![-literals[0], counters[0][0]]);
+        for counter in counters[0].iter().skip(1) {
+            self.add_clause(vec![-counter]);
+        }
+        for i in 1..literals.len() - 1 {
+            self.add_clause(vec![-literals[i], counters[i][0]]);
+            self.add_clause(vec![-counters[i - 1][0], counters[i][0]]);
+            for j in 1..k {
+                self.add_clause(vec![-literals[i], -counters[i - 1][j - 1], counters[i][j]]);
+                self.add_clause(vec![-counters[i - 1][j], counters[i][j]]);
+            }
+            self.add_clause(vec![-literals[i], -counters[i - 1][k - 1]]);
+        }
+        self.add_clause(vec![
+            -literals[literals.len() - 1],
+            -counters[literals.len() - 2][k - 1],
+        ]);
+    }
+
+    // At least k of the literals are true: at most (all but k) of them are false
+    pub fn at_least(&mut self, literals: &[i32], k: usize) {
+        if k > literals.len() {
+            self.add_clause(Vec::new());
+            return;
+        }
+        if k == 1 {
+            self.add_clause(literals.to_vec());
+            return;
+        }
+        let negated: Vec<i32> = literals.iter().map(|literal| -literal).collect();
+        self.at_most(&negated, literals.len() - k);
+    }
+
+    pub fn exactly(&mut self, literals: &[i32], k: usize) {
+        self.at_least(literals, k);
+        self.at_most(literals, k);
+    }
+
+    // The DIMACS format that other SAT solvers read, with the names of the variables as comments
+    pub fn write_dimacs(&self, output: &mut dyn Write) -> io::Result<()> {
+        for (variable, name) in self.names.iter() {
+            writeln!(output, "c {} {}", variable, name)?;
+        }
+        writeln!(output, "p cnf {} {}", self.variables, self.clauses.len())?;
+        for clause in self.clauses.iter() {
+            for literal in clause.iter() {
+                write!(output, "{} ", literal)?;
+            }
+            writeln!(output, "0")?;
+        }
+        Ok(())
+    }
+}
+
+// The clauses for a puzzle, and which candidate word every variable stands for
+pub struct Encoding {
+    pub cnf: Cnf,
+    // Per cipher word: the candidate words and their variables
+    pub candidates: Vec<Vec<(String, i32)>>,
+}
+
+// Every cipher word is exactly one of its candidates, every number stands for exactly as many letters as its budget,
+// every letter is under at most one number, and a letter under a number appears at most that number of times
+// (together with the budgets that means exactly that number of times, as all positions under a number are filled)
+pub fn encode(puzzle: &Puzzle, dictionary: &Dictionary) -> Encoding {
+    let mut cnf = Cnf::default();
+    let empty_solution = solver::empty_solution(&puzzle.input_numbers);
+    let mut counts = solver::LetterCounts::new(&puzzle.input_numbers, &puzzle.number_letter_counts);
+
+    // Variables for the number and letter pairs, and for a letter at a position (per number, to count them)
+    let mut number_letters: BTreeMap<(u32, usize), i32> = BTreeMap::new();
+    let mut position_letters: BTreeMap<(usize, usize, usize), i32> = BTreeMap::new();
+
+    let mut candidates = Vec::new();
+    for (i, numbers) in puzzle.input_numbers.iter().enumerate() {
+        let words: Vec<(String, i32)> = solver::explain_candidates(
+            &empty_solution,
+            &puzzle.input_numbers,
+            &mut counts,
+            &dictionary.words,
+            i,
+        )
+        .into_iter()
+        .filter(|(_, conflict)| conflict.is_none())
+        .map(|(word, _)| {
+            let variable = cnf.new_variable();
+            cnf.names
+                .push((variable, format!("word {} is {}", i + 1, word)));
+            (word.to_string(), variable)
+        })
+        .collect();
+
+        let literals: Vec<i32> = words.iter().map(|(_, variable)| *variable).collect();
+        cnf.exactly(&literals, 1);
+
+        // A candidate puts its letters at the positions of the cipher word, and so under their numbers
+        for (word, variable) in words.iter() {
+            for (position, (number, letter)) in numbers.iter().zip(word.chars()).enumerate() {
+                let letter = letters::index(letter).unwrap();
+                let number_letter = *number_letters.entry((*number, letter)).or_insert_with(|| {
+                    let variable = cnf.new_variable();
+                    cnf.names.push((
+                        variable,
+                        format!("number {} is {}", number, letters::letter(letter)),
+                    ));
+                    variable
+                });
+                let position_letter = *position_letters
+                    .entry((i, position, letter))
+                    .or_insert_with(|| {
+                        let variable = cnf.new_variable();
+                        cnf.add_clause(vec![-variable, number_letter]);
+                        variable
+                    });
+                cnf.add_clause(vec![-variable, position_letter]);
+            }
+        }
+        candidates.push(words);
+    }
+
+    // Every number has exactly its budget of letters
+    for (number, budget) in puzzle.number_letter_counts.iter().enumerate() {
+        let literals: Vec<i32> = number_letters
+            .iter()
+            .filter(|((other, _), _)| *other as usize == number)
+            .map(|(_, variable)| *variable)
+            .collect();
+        if !literals.is_empty() {
+            cnf.exactly(&literals, *budget as usize);
+        }
+    }
+
+    // Every letter is under at most one number
+    for letter in 0..letters::CAPACITY {
+        let literals: Vec<i32> = number_letters
+            .iter()
+            .filter(|((_, other), _)| *other == letter)
+            .map(|(_, variable)| *variable)
+            .collect();
+        cnf.at_most(&literals, 1);
+    }
+
+    // A letter under a number appears at most that number of times
+    let mut occurrences: BTreeMap<(u32, usize), Vec<i32>> = BTreeMap::new();
+    for ((word, position, letter), variable) in position_letters.iter() {
+        let number = puzzle.input_numbers[*word][*position];
+        occurrences
+            .entry((number, *letter))
+            .or_default()
+            .push(*variable);
+    }
+    for ((number, _), literals) in occurrences.iter() {
+        cnf.at_most(literals, *number as usize);
+    }
+
+    Encoding { cnf, candidates }
+}
+
+// The candidate every cipher word got in the SAT solution
+pub fn decode(encoding: &Encoding, model: &[bool]) -> Vec<Vec<char>> {
+    encoding
+        .candidates
+        .iter()
+        .map(|words| {
+            words
+                .iter()
+                .find(|(_, variable)| model[*variable as usize - 1])
+                .map(|(word, _)| word.chars().collect())
+                .unwrap_or_default()
+        })
+        .collect()
+}
@@ -0,0 +1,81 @@
+use crate::solver;
+
+pub struct Encoding {
+    // The plaintext words that were encoded (lowercase, letters only)
+    pub words: Vec<String>,
+    // The cipher text, e.g. 122 11 11 122
+    pub ciphertext: String,
+    // How often each letter appears in the plaintext, sorted by letter
+    pub letter_counts: Vec<(char, usize)>,
+    // Number of different letters each number corresponds to
+    pub number_letter_counts: Vec<u32>,
+}
+
+// Encode a plaintext sentence by replacing every letter with the number of times it appears in the sentence.
+// Numbers of 10 or more are written between brackets.
+pub fn encode(plaintext: &str) -> Encoding {
+    // Only keep the letters of every word, in lowercase
+    let words: Vec<String> = plaintext
+        .split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphabetic())
+                .flat_map(|c| c.to_lowercase())
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    // Count how many times each letter appears
+    let mut letter_counts: Vec<(char, usize)> = Vec::new();
+    for character in words.iter().flat_map(|word| word.chars()) {
+        match letter_counts.iter_mut().find(|(c, _)| *c == character) {
+            Some((_, count)) => *count += 1,
+            None => letter_counts.push((character, 1)),
+        }
+    }
+    letter_counts.sort_unstable();
+
+    let ciphertext = words
+        .iter()
+        .map(|word| {
+            let numbers: Vec<u32> = word
+                .chars()
+                .map(|c| {
+                    let (_, count) = letter_counts.iter().find(|(l, _)| *l == c).unwrap();
+                    *count as u32
+                })
+                .collect();
+            solver::format_numbers(&numbers)
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    let number_letter_counts = solver::get_number_of_characters_per_number_count(&ciphertext);
+
+    Encoding {
+        words,
+        ciphertext,
+        letter_counts,
+        number_letter_counts,
+    }
+}
+
+pub fn run(plaintext: &str) {
+    let encoding = encode(plaintext);
+    println!("Plaintext:  {}", encoding.words.join(" "));
+    println!("Ciphertext: {}", encoding.ciphertext);
+    println!("\nNumber  Letters");
+    for (number, number_letter_count) in encoding.number_letter_counts.iter().enumerate().skip(1) {
+        if *number_letter_count == 0 {
+            continue;
+        }
+        let letters: String = encoding
+            .letter_counts
+            .iter()
+            .filter(|(_, count)| *count == number)
+            .map(|(letter, _)| *letter)
+            .collect();
+        println!("  {}     {} ({})", number, number_letter_count, letters);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::solver;
+use crate::strategy::{Dictionary, Puzzle};
+
+// How many random paths down the search tree are averaged for the estimate
+pub const SAMPLES: usize = 200;
+
+// How big the search is going to be, before actually searching
+pub struct Estimate {
+    // Upper bound: every combination of candidate words (each checked on its own)
+    // None with compounds or unknown words, as the candidates are only whole words from the wordlist
+    pub word_combinations: Option<f64>,
+    // Upper bound: every way to pick different letters for every number within its budget
+    pub letter_assignments: f64,
+    // Knuth's estimate of the number of nodes the backtracking solver visits (a node is a letter that passed the checks)
+    pub nodes: f64,
+}
+
+// Small deterministic random number generator, so the same puzzle always gets the same estimate
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self, max: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % max as u64) as usize
+    }
+}
+
+pub fn estimate(
+    puzzle: &Puzzle,
+    dictionary: &Dictionary,
+    options: &solver::Options,
+    samples: usize,
+) -> Estimate {
+    let solution = solver::empty_solution(&puzzle.input_numbers);
+    let mut counts = solver::LetterCounts::new(&puzzle.input_numbers, &puzzle.number_letter_counts);
+    let word_combinations =
+        (options.max_components == 1 && options.max_unknown_words == 0).then(|| {
+            (0..puzzle.input_numbers.len())
+                .map(|i| {
+                    solver::count_candidates(
+                        &solution,
+                        &puzzle.input_numbers,
+                        &mut counts,
+                        &dictionary.words,
+                        i,
+                    ) as f64
+                })
+                .product()
+        });
+
+    // 26 * 25 * ... (for the letters of the wordlist) for every letter that is needed,
+    // divided by the orderings of the letters under the same number
+    let letters: u32 = puzzle.number_letter_counts.iter().sum();
+    let alphabet = dictionary.tree.letters().len();
+    let mut letter_assignments: f64 = (0..letters)
+        .map(|i| (alphabet - i as usize) as f64)
+        .product();
+    for budget in puzzle.number_letter_counts.iter() {
+        letter_assignments /= (1..=*budget).map(|i| i as f64).product::<f64>();
+    }
+    if letters as usize > alphabet {
+        letter_assignments = 0.0;
+    }
+
+    let mut random = Random(
+        puzzle
+            .input_numbers
+            .iter()
+            .flatten()
+            .map(|n| *n as u64)
+            .sum(),
+    );
+    let nodes = (0..samples)
+        .map(|_| probe(puzzle, dictionary, options, &mut random))
+        .sum::<f64>()
+        / samples.max(1) as f64;
+
+    Estimate {
+        word_combinations,
+        letter_assignments,
+        nodes,
+    }
+}
+
+// Knuth's estimator: walk one random path down the search tree, and count every level as if all its siblings had
+// as many children as the ones on the path (1 + d1 + d1 * d2 + ..., where d is the number of letters that fit)
+fn probe(
+    puzzle: &Puzzle,
+    dictionary: &Dictionary,
+    options: &solver::Options,
+    random: &mut Random,
+) -> f64 {
+    let mut solution = solver::empty_solution(&puzzle.input_numbers);
+
+    // Follow the positions in the same order as the solver
+    let mut nodes = 1.0;
+    let mut width = 1.0;
+    let mut position = (0, 0);
+    while let Some((word, character)) = solver::next_position(
+        &solution,
+        &puzzle.input_numbers,
+        &dictionary.tree,
+        &puzzle.number_letter_counts,
+        options,
+        position.0,
+        position.1,
+    ) {
+        let letters: Vec<char> =
+            solver::candidate_letters(&solution, &dictionary.tree, options, word, character)
+                .into_iter()
+                .filter(|letter| {
+                    solution[word][character] = *letter;
+                    solver::find_conflict(
+                        &solution,
+                        &puzzle.input_numbers,
+                        &dictionary.tree,
+                        &puzzle.number_letter_counts,
+                        options,
+                    )
+                    .is_none()
+                })
+                .collect();
+        if letters.is_empty() {
+            break;
+        }
+        width *= letters.len() as f64;
+        nodes += width;
+        solution[word][character] = letters[random.next(letters.len())];
+        position = (word, character + 1);
+    }
+    nodes
+}
+
+pub fn print_estimate(estimate: &Estimate) {
+    let word_combinations = match estimate.word_combinations {
+        Some(word_combinations) => format!("at most {:.3e}", word_combinations),
+        None => "unbounded".to_string(),
+    };
+    println!(
+        "Estimated search: {:.3e} nodes ({} word combinations, at most {:.3e} letter assignments)",
+        estimate.nodes, word_combinations, estimate.letter_assignments
+    );
+}
@@ -0,0 +1,128 @@
+// A set of letters with one bit per letter, so checking, combining and counting letters are single instructions
+// Words are lowercased when the wordlist is read, so only lowercase letters get a bit:
+// a-z come first, so a set of only those fits in the lowest 32 bits, and the accented letters (à-ÿ) use the bits after that
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LetterSet(u64);
+
+// How many different letters a set can hold
+pub const CAPACITY: usize = 64;
+
+// The bit of a letter, or None for characters that are not in the alphabet
+pub fn index(letter: char) -> Option<usize> {
+    match letter {
+        'a'..='z' => Some(letter as usize - 'a' as usize),
+        'à'..='ÿ' if letter.is_alphabetic() => Some(26 + letter as usize - 'à' as usize),
+        _ => None,
+    }
+}
+
+// The letter of a bit, the other way around
+pub fn letter(index: usize) -> char {
+    match index {
+        0..=25 => (b'a' + index as u8) as char,
+        _ => char::from_u32('à' as u32 + (index - 26) as u32).unwrap(),
+    }
+}
+
+impl LetterSet {
+    pub fn new() -> LetterSet {
+        LetterSet(0)
+    }
+
+    // The 26 letters a-z
+    pub fn alphabet() -> LetterSet {
+        LetterSet((1 << 26) - 1)
+    }
+
+    // Returns false if the character is not a letter (and so can't be added)
+    pub fn insert(&mut self, letter: char) -> bool {
+        match index(letter) {
+            Some(index) => {
+                self.0 |= 1 << index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&mut self, letter: char) {
+        if let Some(index) = index(letter) {
+            self.0 &= !(1 << index);
+        }
+    }
+
+    pub fn contains(&self, letter: char) -> bool {
+        index(letter).is_some_and(|index| self.0 & (1 << index) != 0)
+    }
+
+    pub fn union(self, other: LetterSet) -> LetterSet {
+        LetterSet(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: LetterSet) -> LetterSet {
+        LetterSet(self.0 & other.0)
+    }
+
+    pub fn difference(self, other: LetterSet) -> LetterSet {
+        LetterSet(self.0 & !other.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    // The letters in the set, a-z first
+    pub fn iter(&self) -> impl Iterator<Item = char> {
+        let bits = self.0;
+        (0..CAPACITY)
+            .filter(move |index| bits & (1 << index) != 0)
+            .map(letter)
+    }
+}
+
+impl FromIterator<char> for LetterSet {
+    fn from_iter<I: IntoIterator<Item = char>>(letters: I) -> LetterSet {
+        let mut set = LetterSet::new();
+        for letter in letters {
+            set.insert(letter);
+        }
+        set
+    }
+}
+
+impl std::fmt::Display for LetterSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for letter in self.iter() {
+            write!(f, "{}", letter)?;
+        }
+        Ok(())
+    }
+}
+
+// How often every letter appears in a word, computed once for every word of the wordlist tree
+// Only the letters that appear are stored, as (bit, count) in the order of the bits, a word uses only a few of them
+pub type Signature = [[u8; 2]];
+
+// How often every letter appears, by bit (anything that is not a letter, like an empty position, is skipped)
+pub fn letter_counts(word: &[char]) -> [u8; CAPACITY] {
+    let mut counts = [0u8; CAPACITY];
+    for index in word.iter().filter_map(|letter| index(*letter)) {
+        counts[index] = counts[index].saturating_add(1);
+    }
+    counts
+}
+
+// Add the signature of a word to the end of a list of signatures
+pub fn push_signature(signatures: &mut Vec<[u8; 2]>, word: &[char]) {
+    signatures.extend(
+        letter_counts(word)
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(index, count)| [index as u8, *count]),
+    );
+}
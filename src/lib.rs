@@ -0,0 +1,16 @@
+pub mod analyze;
+pub mod batch;
+pub mod cache;
+pub mod cnf;
+pub mod encoder;
+pub mod estimate;
+pub mod letters;
+pub mod old2solver;
+pub mod oldsolver;
+pub mod repl;
+pub mod sat;
+pub mod solver;
+pub mod strategy;
+pub mod tui;
+pub mod typos;
+pub mod uniqueness;
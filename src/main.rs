@@ -1,20 +1,384 @@
+use aivd_solver::strategy::{self, Puzzle, Source, Strategy};
+use aivd_solver::{analyze, batch, cache, cnf, encoder, estimate, repl, solver, typos, uniqueness};
+use color_eyre::eyre::{eyre, WrapErr};
+use color_eyre::owo_colors::OwoColorize;
 use color_eyre::Report;
 use std::time::Instant;
 
-// Importing the part1 and part2 modules
-mod solver;
+// The wordlist is too big to keep in the repository, so it is read at runtime
+const WORDLIST_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/words.txt");
+
+// The compiled wordlist tree is cached here, so it doesn't have to be rebuilt every run
+const CACHE_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/target/wordlist-cache");
 
 fn main() -> Result<(), Report> {
     color_eyre::install()?;
     let input = include_str!("input.txt");
-    let words = include_str!("words.txt");
+
+    // Interactive modes: `cargo run -- repl` or `cargo run -- tui`
+    // Encoding a sentence: `cargo run -- encode de kat zit op de mat`
+    // Checking a puzzle has one decoding: `cargo run -- check-unique [ciphertext]`
+    // Statistics about a puzzle without solving it: `cargo run -- analyze [ciphertext]`
+    // Writing the puzzle as clauses for an external SAT solver: `cargo run -- cnf puzzle.cnf [ciphertext]` (DIMACS)
+    // Solving many puzzles at once: `cargo run -- batch puzzles.txt` (one per line) or `cargo run -- batch puzzles/` (one per file)
+    // Solving with a specific strategy: `cargo run -- --strategy old2solver` (or `--strategy all` to compare them)
+    // Using more wordlists: `cargo run -- --words src/words.txt:2 --words names.txt:1 --words theme.txt:3`
+    //   (the number after the colon is a priority to rank solutions by, the wordlist is named after the file)
+    // Allowing cipher words to be compounds of up to 3 words from the wordlist: `cargo run -- --compounds 3`
+    // Allowing 1 cipher word that is not in the wordlist at all (a name): `cargo run -- --unknown-words 1`
+    // Solving a ciphertext without spaces between the words: `cargo run -- --no-spaces`
+    // Fixing up to 2 wrongly typed, missing or extra digits: `cargo run -- --typos 2`
+    // Filling in the most constrained position first instead of left to right: `cargo run -- --ordering most-constrained`
+    // Trying common letters first: `cargo run -- --letter-order wordlist` (counted in the wordlist) or `--letter-order enatiro`
+    let mut args: Vec<String> = std::env::args().collect();
+    let strategy_name = take_flag(&mut args, "--strategy").unwrap_or_else(|| "solver".to_string());
+    let mut options = solver::Options::default();
+    if let Some(compounds) = take_flag(&mut args, "--compounds") {
+        options.max_components = compounds
+            .parse()
+            .ok()
+            .filter(|compounds| *compounds > 0)
+            .ok_or_else(|| eyre!("Invalid number of compound parts: {}", compounds))?;
+    }
+    if let Some(unknown_words) = take_flag(&mut args, "--unknown-words") {
+        options.max_unknown_words = unknown_words
+            .parse()
+            .wrap_err_with(|| format!("Invalid number of unknown words: {}", unknown_words))?;
+    }
+    if let Some(order) = take_flag(&mut args, "--ordering") {
+        options.order = solver::Order::parse(&order).ok_or_else(|| {
+            eyre!(
+                "Unknown ordering {}, pick one of: fixed, most-constrained",
+                order
+            )
+        })?;
+    }
+    let letter_order = take_flag(&mut args, "--letter-order");
+    let no_spaces = take_switch(&mut args, "--no-spaces");
+    let max_typos: usize = match take_flag(&mut args, "--typos") {
+        Some(typos) => typos
+            .parse()
+            .wrap_err_with(|| format!("Invalid number of typos: {}", typos))?,
+        None => 0,
+    };
+    let mut wordlist_arguments: Vec<String> = Vec::new();
+    while let Some(argument) = take_flag(&mut args, "--words") {
+        wordlist_arguments.push(argument);
+    }
+    if wordlist_arguments.is_empty() {
+        wordlist_arguments.push(WORDLIST_PATH.to_string());
+    }
+
+    // Read every wordlist, and merge them into one for building the tree
+    let mut wordlists: Vec<(String, i32, String)> = Vec::new();
+    for argument in wordlist_arguments.iter() {
+        // Only a number after the last colon is a priority, otherwise the colon is part of the path
+        let (path, priority) = argument
+            .rsplit_once(':')
+            .and_then(|(path, priority)| Some((path, priority.parse().ok()?)))
+            .unwrap_or((argument.as_str(), 0));
+        let name = std::path::Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+        let wordlist = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Could not read the wordlist from {}", path))?;
+        wordlists.push((name, priority, wordlist));
+    }
+    let sources: Vec<Source> = wordlists
+        .iter()
+        .map(|(name, priority, wordlist)| Source {
+            name: name.clone(),
+            priority: *priority,
+            wordlist,
+        })
+        .collect();
+    let merged_words = wordlists
+        .iter()
+        .map(|(_, _, wordlist)| wordlist.as_str())
+        .collect::<Vec<&str>>()
+        .join("\n");
+    let words = merged_words.as_str();
+
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("repl") => {
+            repl::run(input, words, false);
+            return Ok(());
+        }
+        Some("tui") => {
+            repl::run(input, words, true);
+            return Ok(());
+        }
+        Some("encode") => {
+            encoder::run(&args[2..].join(" "));
+            return Ok(());
+        }
+        Some("check-unique") => {
+            // Check the given ciphertext, or the input if there is none
+            let ciphertext = args[2..].join(" ");
+            if ciphertext.is_empty() {
+                uniqueness::run(input, words);
+            } else {
+                uniqueness::run(&ciphertext, words);
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let strategies: Vec<Box<dyn Strategy>> = if strategy_name == "all" {
+        strategy::strategies()
+    } else {
+        let strategy = strategy::find_strategy(&strategy_name).ok_or_else(|| {
+            let names: Vec<&str> = strategy::strategies().iter().map(|s| s.name()).collect();
+            eyre!(
+                "Unknown strategy {}, pick one of: {}, all",
+                strategy_name,
+                names.join(", ")
+            )
+        })?;
+        vec![strategy]
+    };
 
     let start = Instant::now();
-    let result = solver::solve(&input, &words);
+    let mut dictionary = cache::load_or_build(words, std::path::Path::new(CACHE_DIRECTORY));
+    // With a single wordlist every word comes from it, so there is nothing to rank by
+    if sources.len() > 1 {
+        dictionary.tag_sources(&sources);
+    }
+    println!("Loading took: {:?}", start.elapsed().as_micros());
+
+    match letter_order.as_deref() {
+        Some("wordlist") => {
+            options.letter_order = solver::letter_order_from_wordlist(&dictionary.words);
+            println!(
+                "Letter order: {}",
+                options.letter_order.iter().collect::<String>()
+            );
+        }
+        Some(letters) => options.letter_order = solver::letter_order(letters),
+        None => {}
+    }
+
+    // Commands that need the dictionary
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("batch") => {
+            let path = args
+                .get(2)
+                .ok_or_else(|| eyre!("Give a file or directory with puzzles to solve"))?;
+            // Fixing typos means solving many variants of a single ciphertext, which is not done for a whole batch
+            if max_typos > 0 {
+                return Err(eyre!("--typos can't be combined with batch"));
+            }
+            let puzzles = batch::load_puzzles(std::path::Path::new(path))
+                .wrap_err_with(|| format!("Could not read the puzzles from {}", path))?;
+            for strategy in strategies {
+                println!("\n{} ({})", strategy.name(), strategy.description());
+                let results = batch::solve_all(
+                    &puzzles,
+                    strategy.as_ref(),
+                    &dictionary,
+                    &options,
+                    no_spaces,
+                );
+                batch::print_summary(&results);
+            }
+            return Ok(());
+        }
+        Some("analyze") => {
+            // Analyze the given ciphertext, or the input if there is none
+            let ciphertext = args[2..].join(" ");
+            let ciphertext = if ciphertext.is_empty() {
+                input
+            } else {
+                ciphertext.as_str()
+            };
+            analyze::print_report(ciphertext, &analyze::analyze(ciphertext, &dictionary));
+            return Ok(());
+        }
+        Some("cnf") => {
+            // Encode the given ciphertext, or the input if there is none
+            let path = args
+                .get(2)
+                .ok_or_else(|| eyre!("Give a file to write the DIMACS clauses to"))?;
+            let ciphertext = args[3..].join(" ");
+            let ciphertext = if ciphertext.is_empty() {
+                input
+            } else {
+                ciphertext.as_str()
+            };
+            let encoding = cnf::encode(&Puzzle::parse(ciphertext), &dictionary);
+            let mut file = std::fs::File::create(path)
+                .wrap_err_with(|| format!("Could not create {}", path))?;
+            encoding
+                .cnf
+                .write_dimacs(&mut file)
+                .wrap_err_with(|| format!("Could not write to {}", path))?;
+            println!(
+                "Wrote {} variables and {} clauses to {}",
+                encoding.cnf.variables,
+                encoding.cnf.clauses.len(),
+                path
+            );
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let parse = |ciphertext: &str| {
+        if no_spaces {
+            Puzzle::parse_without_spaces(ciphertext)
+        } else {
+            Puzzle::parse(ciphertext)
+        }
+    };
+    if no_spaces {
+        // Every letter could be a word on its own, so allow as many words as there are numbers (and missing numbers)
+        options.max_components = solver::parse_input(input)
+            .iter()
+            .map(|word| word.len())
+            .sum::<usize>()
+            + max_typos;
+    }
+
+    // A number that no count can reach is malformed, unless it can be fixed as a typo
+    if let (0, Some(number)) = (max_typos, solver::oversized_numbers(input).first()) {
+        return Err(eyre!(
+            "{} is larger than the number of numbers in the ciphertext",
+            number
+        ));
+    }
+
+    // Find the fewest typos that make the ciphertext solvable, and solve that one instead
+    let mut ciphertext = input.to_string();
+    if max_typos > 0 {
+        let start = Instant::now();
+        let correction = typos::correct(input, max_typos, &mut |ciphertext| {
+            strategy::Backtracking
+                .solve(&parse(ciphertext), &dictionary, &options)
+                .solution
+                .is_some()
+        });
+        match correction {
+            Some(correction) if correction.edits.is_empty() => println!("No typos found"),
+            Some(correction) => {
+                println!("Typos fixed: {}", correction.ciphertext);
+                for edit in correction.edits.iter() {
+                    println!("  {}", edit);
+                }
+                ciphertext = correction.ciphertext;
+            }
+            None => println!("No solution with at most {} typos", max_typos),
+        }
+        println!("Fixing typos took: {:?}", start.elapsed().as_micros());
+    }
+    let puzzle = parse(&ciphertext);
+    println!("Number letter counts: {:?}", puzzle.number_letter_counts);
+    estimate::print_estimate(&estimate::estimate(
+        &puzzle,
+        &dictionary,
+        &options,
+        estimate::SAMPLES,
+    ));
 
-    let duration = start.elapsed();
-    println!("\nResult: {:?}", result);
-    println!("Time taken: {:?}", duration.as_micros());
+    for strategy in strategies {
+        println!("\n{} ({})", strategy.name(), strategy.description());
+
+        let start = Instant::now();
+        let outcome = strategy.solve(&puzzle, &dictionary, &options);
+        let duration = start.elapsed();
+
+        match outcome.solution {
+            Some(solution) => {
+                let words: Vec<String> =
+                    solution.iter().map(|word| word.iter().collect()).collect();
+                // Without spaces the words are shown below, otherwise keep the punctuation and line breaks of the input
+                if no_spaces {
+                    println!("Solution: {}", words.join(" "));
+                } else {
+                    println!("Solution: {}", solver::render(&ciphertext, &solution));
+                }
+
+                // The unknown words only follow the numbers, their letters are one of many possible fillings
+                let unknown_words = solver::unknown_words(&solution, &dictionary.tree, &options);
+                if !unknown_words.is_empty() {
+                    let known_words: Vec<String> = words
+                        .iter()
+                        .enumerate()
+                        .map(|(i, word)| {
+                            if unknown_words.contains(&i) {
+                                "?".repeat(word.len())
+                            } else {
+                                word.clone()
+                            }
+                        })
+                        .collect();
+                    println!("Known words: {}", known_words.join(" "));
+                    for i in unknown_words.iter() {
+                        println!(
+                            "  Word {} is not in the wordlist (filled in as {})",
+                            i + 1,
+                            words[*i]
+                        );
+                    }
+                }
+
+                // Show how the numbers were split into words, or how the compound words were split
+                if no_spaces {
+                    println!("Words: {}", outcome.words.concat().join(" "));
+                } else if options.max_components > 1 {
+                    for (word, components) in words.iter().zip(outcome.words.iter()) {
+                        if components.len() > 1 {
+                            println!("  {}: {}", word, components.join(" + "));
+                        }
+                    }
+                }
+
+                // Show which wordlists the words came from when there is more than one
+                if sources.len() > 1 {
+                    println!("Score: {}", dictionary.score(&outcome.words));
+                    for word in words.iter() {
+                        println!("  {}: {}", word, dictionary.sources_of(word).join(", "));
+                    }
+                }
+            }
+            None if outcome.candidate_counts.is_none() => println!("No solution found"),
+            None => {}
+        }
+        if let Some(candidate_counts) = outcome.candidate_counts {
+            println!("Candidates per word: {:?}", candidate_counts);
+        }
+        if outcome.truncated {
+            println!(
+                "{} only the first {} solutions were ranked, a better one may have been missed",
+                "Warning:".bright_yellow(),
+                strategy::RANKED_SOLUTIONS
+            );
+        }
+        println!("Time taken: {:?}", duration.as_micros());
+    }
 
     Ok(())
 }
+
+// Remove `--flag value` from the arguments and return the value
+fn take_flag(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    args.remove(index);
+    if index < args.len() {
+        Some(args.remove(index))
+    } else {
+        None
+    }
+}
+
+// Remove `--flag` from the arguments and return if it was there
+fn take_switch(args: &mut Vec<String>, flag: &str) -> bool {
+    match args.iter().position(|arg| arg == flag) {
+        Some(index) => {
+            args.remove(index);
+            true
+        }
+        None => false,
+    }
+}
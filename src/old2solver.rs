@@ -1,10 +1,12 @@
+use crate::letters::LetterSet;
+
 // Struct to hold each input 'word' (list of numbers)
-struct Word {
-    numbers: Vec<u8>,
-    possible_words: Vec<String>,
+pub struct Word {
+    pub numbers: Vec<u32>,
+    pub possible_words: Vec<String>,
 }
 
-fn filter_words_globally(input_numbers: &Vec<Vec<u8>>, possible_words: &mut Vec<&str>) {
+pub fn filter_words_globally(input_numbers: &[Vec<u32>], possible_words: &mut Vec<&str>) {
     // Get list of unique input word lengths
     let mut word_lengths: Vec<usize> = input_numbers.iter().map(|word| word.len()).collect();
     word_lengths.sort_unstable();
@@ -20,11 +22,11 @@ fn filter_words_globally(input_numbers: &Vec<Vec<u8>>, possible_words: &mut Vec<
     });
 }
 
-fn refine_possible_words(input_words: &mut Vec<Word>, possible_letters: &Vec<Vec<char>>) {
+pub fn refine_possible_words(input_words: &mut [Word], possible_letters: &[LetterSet]) {
     for word in input_words.iter_mut() {
         word.possible_words.retain(|possible_word| {
-            let mut letter_map = vec![None; 10];
-            let mut used_letters_for_1 = Vec::new();
+            let mut letter_map = vec![None; possible_letters.len()];
+            let mut used_letters_for_1 = LetterSet::new();
 
             for (number, letter) in word.numbers.iter().zip(possible_word.chars()) {
                 let number = *number as usize;
@@ -32,7 +34,7 @@ fn refine_possible_words(input_words: &mut Vec<Word>, possible_letters: &Vec<Vec
                 // Check for number 7 and 8 specific rules
                 if number == 7 || number == 8 {
                     // If the letter is not in the possible letters for this number, discard the word
-                    if !possible_letters[number].contains(&letter) {
+                    if !possible_letters[number].contains(letter) {
                         return false;
                     }
 
@@ -50,13 +52,13 @@ fn refine_possible_words(input_words: &mut Vec<Word>, possible_letters: &Vec<Vec
                     }
                 } else if number == 1 {
                     // For number 1, ensure all occurrences map to different letters
-                    if used_letters_for_1.contains(&letter) {
+                    if used_letters_for_1.contains(letter) {
                         return false;
                     }
-                    used_letters_for_1.push(letter);
+                    used_letters_for_1.insert(letter);
                 } else {
                     // For other numbers, check if the letter is in the possible letters
-                    if !possible_letters[number].contains(&letter) {
+                    if !possible_letters[number].contains(letter) {
                         return false;
                     }
                 }
@@ -67,48 +69,39 @@ fn refine_possible_words(input_words: &mut Vec<Word>, possible_letters: &Vec<Vec
     }
 }
 
-fn update_possible_letters(input_words: &[Word], possible_letters: &mut Vec<Vec<char>>) {
-    // Initialize possible letters for each number as empty
-    for letters in possible_letters.iter_mut() {
-        letters.clear();
-    }
-
+pub fn update_possible_letters(input_words: &[Word], possible_letters: &mut [LetterSet]) {
     // Iterate through each number
-    for number in 0..10 {
-        let mut all_letters_for_number: Vec<char> = Vec::new();
+    for (number, possible_letters) in possible_letters.iter_mut().enumerate() {
+        let mut all_letters_for_number = LetterSet::new();
 
         // Collect letters for this number from each word
         for word in input_words {
             for (index, &num) in word.numbers.iter().enumerate() {
                 if num as usize == number {
                     // Collect all letters that appear at this index in possible words
-                    let letters_at_index: Vec<char> = word
+                    let letters_at_index: LetterSet = word
                         .possible_words
                         .iter()
                         .map(|word| word.chars().nth(index).unwrap())
                         .collect();
 
-                    all_letters_for_number.extend(letters_at_index);
+                    all_letters_for_number = all_letters_for_number.union(letters_at_index);
                 }
             }
         }
 
-        // Deduplicate letters for this number
-        all_letters_for_number.sort_unstable();
-        all_letters_for_number.dedup();
-
         // Assign to possible_letters
-        possible_letters[number] = all_letters_for_number;
+        *possible_letters = all_letters_for_number;
     }
 }
 
-fn further_refine_possible_words(input_words: &mut Vec<Word>, possible_letters: &Vec<Vec<char>>) {
+pub fn further_refine_possible_words(input_words: &mut [Word], possible_letters: &[LetterSet]) {
     for word in input_words.iter_mut() {
         word.possible_words.retain(|possible_word| {
             // Remove any words that have a letter that is not in the possible letters for that number
             for (number, letter) in word.numbers.iter().zip(possible_word.chars()) {
                 let number = *number as usize;
-                if !possible_letters[number].contains(&letter) {
+                if !possible_letters[number].contains(letter) {
                     return false;
                 }
             }
@@ -117,31 +110,14 @@ fn further_refine_possible_words(input_words: &mut Vec<Word>, possible_letters:
     }
 }
 
-pub fn solve(input: &str, wordlist: &str) -> i64 {
-    // Input example: 58 2741 3335
-    // Seperate the input into a vector of vectors of numbers
-    let input_numbers: Vec<Vec<u8>> = input
-        .split_whitespace()
-        .map(|word| {
-            word.chars()
-                .map(|c| c.to_digit(10).unwrap() as u8)
-                .collect()
-        })
-        .collect();
-
-    // Collect all available words into a vector
-    let mut possible_words: Vec<&str> = wordlist.lines().collect();
-    println!("Starting: {:?}", possible_words.len());
-
-    // Filter words from the wordlist that have a length in the word_lengths vector
-    filter_words_globally(&input_numbers, &mut possible_words);
-    println!("Filtered words count: {}", possible_words.len());
-
-    // For every number 0-9 create a list of possible letters (initially all 26)
-    let mut possible_letters: Vec<Vec<char>> =
-        vec!["abcdefghijklmnopqrstuvwxyz".chars().collect::<Vec<char>>(); 10];
+// The lists indexed by number need room for 0-9, and for any larger number in the input
+fn number_slots(input_numbers: &[Vec<u32>]) -> usize {
+    let largest = input_numbers.iter().flatten().copied().max().unwrap_or(0) as usize;
+    (largest + 1).max(10)
+}
 
-    // Create a word struct for each input word, and add the possible words to the struct with the same length as the input word
+// Create a word struct for each input word, and add the possible words to the struct with the same length as the input word
+pub fn create_words(input_numbers: &[Vec<u32>], possible_words: &[&str]) -> Vec<Word> {
     let mut input_words: Vec<Word> = Vec::new();
     for input_word in input_numbers.iter() {
         let mut word = Word {
@@ -155,9 +131,46 @@ pub fn solve(input: &str, wordlist: &str) -> i64 {
             .collect();
         input_words.push(word);
     }
+    input_words
+}
+
+// Run every filtering step without printing anything in between
+pub fn filter_candidates(input_numbers: &[Vec<u32>], words: &[impl AsRef<str>]) -> Vec<Word> {
+    let mut possible_words: Vec<&str> = words.iter().map(AsRef::as_ref).collect();
+    filter_words_globally(input_numbers, &mut possible_words);
+
+    let mut possible_letters: Vec<LetterSet> =
+        vec![LetterSet::alphabet(); number_slots(input_numbers)];
+    let mut input_words = create_words(input_numbers, &possible_words);
+
+    refine_possible_words(&mut input_words, &possible_letters);
+    update_possible_letters(&input_words, &mut possible_letters);
+    further_refine_possible_words(&mut input_words, &possible_letters);
+
+    input_words
+}
+
+pub fn solve(input: &str, wordlist: &str) -> i64 {
+    // Input example: 58 2741 3335
+    // Seperate the input into a vector of vectors of numbers
+    let input_numbers: Vec<Vec<u32>> = crate::solver::parse_input(input);
+
+    // Collect all available words into a vector
+    let mut possible_words: Vec<&str> = wordlist.lines().collect();
+    println!("Starting: {:?}", possible_words.len());
+
+    // Filter words from the wordlist that have a length in the word_lengths vector
+    filter_words_globally(&input_numbers, &mut possible_words);
+    println!("Filtered words count: {}", possible_words.len());
+
+    // For every number create a list of possible letters (initially all 26)
+    let mut possible_letters: Vec<LetterSet> =
+        vec![LetterSet::alphabet(); number_slots(&input_numbers)];
+
+    let mut input_words = create_words(&input_numbers, &possible_words);
 
     // Count how many times each number appears in the input
-    let mut number_counts: Vec<u8> = vec![0; 10];
+    let mut number_counts: Vec<u32> = vec![0; number_slots(&input_numbers)];
     for input_word in input_numbers.iter() {
         for number in input_word.iter() {
             number_counts[*number as usize] += 1;
@@ -170,12 +183,12 @@ pub fn solve(input: &str, wordlist: &str) -> i64 {
 
     // Get the number of letters for each number (if the number 3 appears 9 times, then there the number 3 can correspond to 3 different letters)
     // If the number 7 appears 7 times, then there is only 1 letter the number 7 can correspond to
-    let mut number_letter_counts: Vec<u8> = vec![0; 10];
+    let mut number_letter_counts: Vec<u32> = vec![0; number_counts.len()];
     for (i, number_count) in number_counts.iter().enumerate() {
         if i == 0 {
             continue;
         }
-        number_letter_counts[i] = *number_count / (i as u8);
+        number_letter_counts[i] = *number_count / (i as u32);
     }
 
     println!("Number of letters per number counts:");
@@ -189,23 +202,19 @@ pub fn solve(input: &str, wordlist: &str) -> i64 {
     for word in input_words.iter() {
         println!(
             "{}: {}",
-            word.numbers
-                .iter()
-                .map(|n| n.to_string())
-                .collect::<Vec<String>>()
-                .join(""),
+            crate::solver::format_numbers(&word.numbers),
             word.possible_words.len()
         );
     }
 
-    refine_possible_words(&mut input_words, &mut possible_letters);
+    refine_possible_words(&mut input_words, &possible_letters);
 
     // Update the possible letters for each number based on the possible words
     update_possible_letters(&input_words, &mut possible_letters);
 
     // Print all possible letters for each number
     for (i, possible_letter) in possible_letters.iter().enumerate() {
-        println!("{}: {:?}", i, possible_letter);
+        println!("{}: {}", i, possible_letter);
     }
     println!();
 
@@ -214,11 +223,7 @@ pub fn solve(input: &str, wordlist: &str) -> i64 {
     for word in input_words.iter() {
         println!(
             "{}: {}",
-            word.numbers
-                .iter()
-                .map(|n| n.to_string())
-                .collect::<Vec<String>>()
-                .join(""),
+            crate::solver::format_numbers(&word.numbers),
             word.possible_words.len()
         );
     }
@@ -230,11 +235,7 @@ pub fn solve(input: &str, wordlist: &str) -> i64 {
     for word in input_words.iter() {
         println!(
             "{}: {}",
-            word.numbers
-                .iter()
-                .map(|n| n.to_string())
-                .collect::<Vec<String>>()
-                .join(""),
+            crate::solver::format_numbers(&word.numbers),
             word.possible_words.len()
         );
     }
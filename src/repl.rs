@@ -0,0 +1,472 @@
+use color_eyre::owo_colors::OwoColorize;
+use std::borrow::Cow;
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+
+use crate::letters;
+use crate::solver;
+use crate::tui;
+
+// How often the terminal UI is redrawn while the solver is searching
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+const HELP: &str = "Commands:
+  show                      Show the current state
+  set <word> <pos> <letter> Set the letter at a position (1-based)
+  unset <word> <pos>        Clear the letter at a position
+  word <word> <text>        Set a whole word (use . for unknown letters)
+  clear <word>              Clear a whole word
+  reset                     Clear everything
+  undo / redo               Undo or redo the last change
+  solve                     Let the solver complete the current state
+  explain on|off            Record why the solver ruled out letters during solve
+  why                       Summarise the recorded eliminations per constraint
+  why <word>                Show why words from the wordlist don't fit a cipher word
+  why <word> <pos>          Show why the solver ruled out letters at a position
+  help                      Show this help
+  quit                      Exit";
+
+struct State<'a> {
+    input_numbers: Vec<Vec<u32>>,
+    wordlist: Vec<Cow<'a, str>>,
+    wordlist_tree: solver::Tree,
+    number_letter_counts: Vec<u32>,
+    solution: Vec<Vec<char>>,
+    undo_stack: Vec<Vec<Vec<char>>>,
+    redo_stack: Vec<Vec<Vec<char>>>,
+    tui: bool,
+    explain: bool,
+    eliminations: Vec<solver::Elimination>,
+}
+
+impl State<'_> {
+    // Apply a change to the solution, remembering the previous state so it can be undone
+    fn apply(&mut self, new_solution: Vec<Vec<char>>) {
+        let old_solution = std::mem::replace(&mut self.solution, new_solution);
+        self.undo_stack.push(old_solution);
+        self.redo_stack.clear();
+    }
+
+    fn undo(&mut self) -> bool {
+        match self.undo_stack.pop() {
+            Some(previous) => {
+                let current = std::mem::replace(&mut self.solution, previous);
+                self.redo_stack.push(current);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            Some(next) => {
+                let current = std::mem::replace(&mut self.solution, next);
+                self.undo_stack.push(current);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // The letters filled in so far, to check the words from the wordlist against
+    fn letter_counts(&self) -> solver::LetterCounts {
+        solver::LetterCounts::from_solution(
+            &self.solution,
+            &self.input_numbers,
+            &self.number_letter_counts,
+        )
+        .0
+    }
+
+    fn show(&self) {
+        let mut counts = self.letter_counts();
+        let candidate_counts: Vec<usize> = (0..self.solution.len())
+            .map(|i| {
+                solver::count_candidates(
+                    &self.solution,
+                    &self.input_numbers,
+                    &mut counts,
+                    &self.wordlist,
+                    i,
+                )
+            })
+            .collect();
+        let conflict = solver::find_conflict(
+            &self.solution,
+            &self.input_numbers,
+            &self.wordlist_tree,
+            &self.number_letter_counts,
+            &solver::Options::default(),
+        );
+
+        if self.tui {
+            let status = match conflict {
+                Some(conflict) => format!("{} {}", "Inconsistent:".bright_red(), conflict),
+                None => "Consistent".bright_green().to_string(),
+            };
+            tui::draw(
+                &self.input_numbers,
+                &self.solution,
+                &self.number_letter_counts,
+                self.wordlist_tree.letters(),
+                Some(&candidate_counts),
+                &status,
+            );
+            return;
+        }
+
+        for (i, (numbers, word)) in self
+            .input_numbers
+            .iter()
+            .zip(self.solution.iter())
+            .enumerate()
+        {
+            let numbers = solver::format_numbers(numbers);
+            let letters: String = word
+                .iter()
+                .map(|c| if *c == ' ' { '.' } else { *c })
+                .collect();
+            let candidates = if candidate_counts[i] == 0 {
+                format!("{} candidates", candidate_counts[i])
+                    .bright_red()
+                    .to_string()
+            } else {
+                format!("{} candidates", candidate_counts[i])
+            };
+            println!(
+                "{:>3}: {} {} {}",
+                i + 1,
+                numbers,
+                letters.bright_green(),
+                candidates
+            );
+        }
+
+        match conflict {
+            Some(conflict) => println!("{} {}", "Inconsistent:".bright_red(), conflict),
+            None => println!("{}", "Consistent".bright_green()),
+        }
+    }
+
+    // Show how many letters each constraint ruled out during the last solve
+    fn why_summary(&self) {
+        if self.eliminations.is_empty() {
+            println!("Nothing recorded, use explain on and solve first");
+            return;
+        }
+        let mut kinds: Vec<(&str, usize)> = Vec::new();
+        for elimination in self.eliminations.iter() {
+            let kind = elimination.conflict.kind();
+            match kinds.iter_mut().find(|(k, _)| *k == kind) {
+                Some((_, count)) => *count += 1,
+                None => kinds.push((kind, 1)),
+            }
+        }
+        kinds.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        println!("{} letters ruled out:", self.eliminations.len());
+        for (kind, count) in kinds {
+            println!("  {:<24} {}", kind, count);
+        }
+    }
+
+    // Show why the words from the wordlist with the right length don't fit the cipher word
+    fn why_word(&self, word: usize) {
+        let explanations = solver::explain_candidates(
+            &self.solution,
+            &self.input_numbers,
+            &mut self.letter_counts(),
+            &self.wordlist,
+            word,
+        );
+        let fitting = explanations.iter().filter(|(_, c)| c.is_none()).count();
+        println!(
+            "{} of {} words with {} letters fit",
+            fitting,
+            explanations.len(),
+            self.solution[word].len()
+        );
+        for (candidate, conflict) in explanations.iter().take(20) {
+            match conflict {
+                Some(conflict) => println!("  {} {}", candidate.bright_red(), conflict),
+                None => println!("  {}", candidate.bright_green()),
+            }
+        }
+        if explanations.len() > 20 {
+            println!("  ... {} more", explanations.len() - 20);
+        }
+    }
+
+    // Show which letters the last solve ruled out at a position, and the last reason for each
+    fn why_position(&self, word: usize, position: usize) {
+        let mut letters: Vec<(char, usize, &solver::Conflict)> = Vec::new();
+        for elimination in self
+            .eliminations
+            .iter()
+            .filter(|e| e.word == word && e.character == position)
+        {
+            match letters
+                .iter_mut()
+                .find(|(l, _, _)| *l == elimination.letter)
+            {
+                Some((_, count, conflict)) => {
+                    *count += 1;
+                    *conflict = &elimination.conflict;
+                }
+                None => letters.push((elimination.letter, 1, &elimination.conflict)),
+            }
+        }
+        if letters.is_empty() {
+            println!(
+                "Nothing ruled out at word {} position {}",
+                word + 1,
+                position + 1
+            );
+            return;
+        }
+        letters.sort_by_key(|(letter, _, _)| *letter);
+        for (letter, count, conflict) in letters {
+            println!("  {} ({}x) {}", letter.bright_red(), count, conflict);
+        }
+    }
+
+    // Parse a 1-based word number
+    fn word_index(&self, argument: Option<&str>) -> Result<usize, String> {
+        let index: usize = argument
+            .ok_or("Missing word number")?
+            .parse()
+            .map_err(|_| "Word number is not a number")?;
+        if index == 0 || index > self.solution.len() {
+            return Err(format!(
+                "Word number must be between 1 and {}",
+                self.solution.len()
+            ));
+        }
+        Ok(index - 1)
+    }
+
+    // Parse a 1-based position within a word
+    fn position_index(&self, word_index: usize, argument: Option<&str>) -> Result<usize, String> {
+        let length = self.solution[word_index].len();
+        let index: usize = argument
+            .ok_or("Missing position")?
+            .parse()
+            .map_err(|_| "Position is not a number")?;
+        if index == 0 || index > length {
+            return Err(format!("Position must be between 1 and {}", length));
+        }
+        Ok(index - 1)
+    }
+
+    // Run a single command, returns false when the REPL should stop
+    fn execute(&mut self, line: &str) -> Result<bool, String> {
+        let mut arguments = line.split_whitespace();
+        let command = match arguments.next() {
+            Some(command) => command,
+            None => return Ok(true),
+        };
+
+        match command {
+            "show" => {}
+            "set" => {
+                let word = self.word_index(arguments.next())?;
+                let position = self.position_index(word, arguments.next())?;
+                let letter = arguments
+                    .next()
+                    .and_then(|letter| letter.chars().next())
+                    .ok_or("Missing letter")?;
+                let letter = letter.to_lowercase().next().unwrap_or(letter);
+                if letters::index(letter).is_none() {
+                    return Err(format!("{} is not a letter", letter));
+                }
+                let mut solution = self.solution.clone();
+                solution[word][position] = letter;
+                self.apply(solution);
+            }
+            "unset" => {
+                let word = self.word_index(arguments.next())?;
+                let position = self.position_index(word, arguments.next())?;
+                let mut solution = self.solution.clone();
+                solution[word][position] = ' ';
+                self.apply(solution);
+            }
+            "word" => {
+                let word = self.word_index(arguments.next())?;
+                let text: Vec<char> = arguments
+                    .next()
+                    .ok_or("Missing text")?
+                    .chars()
+                    .map(|c| {
+                        if c == '.' {
+                            ' '
+                        } else {
+                            c.to_lowercase().next().unwrap_or(c)
+                        }
+                    })
+                    .collect();
+                if let Some(c) = text
+                    .iter()
+                    .find(|c| **c != ' ' && letters::index(**c).is_none())
+                {
+                    return Err(format!("{} is not a letter", c));
+                }
+                if text.len() != self.solution[word].len() {
+                    return Err(format!(
+                        "Word {} has {} letters, not {}",
+                        word + 1,
+                        self.solution[word].len(),
+                        text.len()
+                    ));
+                }
+                let mut solution = self.solution.clone();
+                solution[word] = text;
+                self.apply(solution);
+            }
+            "clear" => {
+                let word = self.word_index(arguments.next())?;
+                let mut solution = self.solution.clone();
+                solution[word] = vec![' '; solution[word].len()];
+                self.apply(solution);
+            }
+            "reset" => self.apply(solver::empty_solution(&self.input_numbers)),
+            "undo" => {
+                if !self.undo() {
+                    return Err("Nothing to undo".to_string());
+                }
+            }
+            "redo" => {
+                if !self.redo() {
+                    return Err("Nothing to redo".to_string());
+                }
+            }
+            "solve" => {
+                let mut solution = self.solution.clone();
+
+                // In the terminal UI, show the search as it happens
+                let mut tried = 0;
+                let mut last_draw = Instant::now();
+                let mut eliminations = Vec::new();
+                let mut progress = |step: &solver::Step| {
+                    tried += 1;
+                    if let (true, Some(conflict)) = (self.explain, step.conflict) {
+                        eliminations.push(solver::Elimination {
+                            word: step.word,
+                            character: step.character,
+                            letter: step.solution[step.word][step.character],
+                            conflict: conflict.clone(),
+                        });
+                    }
+                    if self.tui && last_draw.elapsed() >= REDRAW_INTERVAL {
+                        tui::draw(
+                            &self.input_numbers,
+                            step.solution,
+                            &self.number_letter_counts,
+                            self.wordlist_tree.letters(),
+                            None,
+                            &format!("Searching... {} letters tried", tried),
+                        );
+                        last_draw = Instant::now();
+                    }
+                };
+
+                let solved = solver::backtrack_recursive(
+                    &mut solution,
+                    &self.input_numbers,
+                    &self.wordlist_tree,
+                    &self.number_letter_counts,
+                    &solver::Options::default(),
+                    0,
+                    0,
+                    &mut progress,
+                    &mut |_| true,
+                );
+                if self.explain {
+                    self.eliminations = eliminations;
+                }
+                if solved {
+                    self.apply(solution);
+                } else {
+                    return Err("No solution from the current state".to_string());
+                }
+            }
+            "explain" => {
+                self.explain = match arguments.next() {
+                    Some("on") => true,
+                    Some("off") => false,
+                    _ => return Err("Use explain on or explain off".to_string()),
+                };
+                println!(
+                    "Explanations are {}",
+                    if self.explain { "on" } else { "off" }
+                );
+                return Ok(true);
+            }
+            "why" => {
+                match arguments.next() {
+                    None => self.why_summary(),
+                    Some(word) => {
+                        let word = self.word_index(Some(word))?;
+                        match arguments.next() {
+                            None => self.why_word(word),
+                            Some(position) => {
+                                let position = self.position_index(word, Some(position))?;
+                                self.why_position(word, position);
+                            }
+                        }
+                    }
+                }
+                return Ok(true);
+            }
+            "help" => {
+                println!("{}", HELP);
+                return Ok(true);
+            }
+            "quit" | "exit" => return Ok(false),
+            _ => return Err(format!("Unknown command: {} (try help)", command)),
+        }
+
+        self.show();
+        Ok(true)
+    }
+}
+
+pub fn run(input: &str, wordlist: &str, tui: bool) {
+    let wordlist = solver::filter_wordlist(wordlist);
+    let wordlist_tree = solver::construct_tree(wordlist.clone());
+    let input_numbers = solver::parse_input(input);
+
+    let mut state = State {
+        solution: solver::empty_solution(&input_numbers),
+        input_numbers,
+        wordlist,
+        wordlist_tree,
+        number_letter_counts: solver::get_number_of_characters_per_number_count(input),
+        undo_stack: Vec::new(),
+        redo_stack: Vec::new(),
+        tui,
+        explain: false,
+        eliminations: Vec::new(),
+    };
+
+    if !state.tui {
+        println!("Number letter counts: {:?}", state.number_letter_counts);
+    }
+    state.show();
+
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            break;
+        }
+
+        match state.execute(&line) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(message) => println!("{}", message.bright_red()),
+        }
+    }
+}
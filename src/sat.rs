@@ -0,0 +1,323 @@
+use std::collections::BinaryHeap;
+
+use crate::cnf::Cnf;
+
+// A small CDCL SAT solver: unit propagation with two watched literals, learning a clause from every conflict
+// (first unique implication point), jumping back to where the learned clause becomes unit, picking the variable
+// that was in the most recent conflicts, and restarting now and then (Luby sequence)
+
+// Conflicts before the first restart, later restarts are multiples of this
+const RESTART_INTERVAL: u64 = 100;
+
+// How much the activity of older conflicts fades
+const ACTIVITY_DECAY: f64 = 0.95;
+
+// Literals are DIMACS style (variable 1 is 1 when true, -1 when false), here stored as 2 * variable + (1 if false)
+fn code(literal: i32) -> usize {
+    2 * (literal.unsigned_abs() as usize - 1) + (literal < 0) as usize
+}
+
+fn variable(literal: i32) -> usize {
+    literal.unsigned_abs() as usize - 1
+}
+
+// 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, ...
+fn luby(mut i: u64) -> u64 {
+    let mut size = 1;
+    let mut power = 1;
+    while size < i + 1 {
+        size = 2 * size + 1;
+        power *= 2;
+    }
+    while size - 1 != i {
+        size = (size - 1) / 2;
+        power /= 2;
+        i %= size;
+    }
+    power
+}
+
+struct Solver {
+    clauses: Vec<Vec<i32>>,
+    // For every literal, the clauses that watch it (the first two literals of a clause are watched)
+    watches: Vec<Vec<usize>>,
+    // Per variable: 1 true, -1 false, 0 unknown, the decision level and the clause that implied it
+    values: Vec<i8>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    // The assigned literals in order, where every decision level starts, and the next one to propagate
+    trail: Vec<i32>,
+    trail_starts: Vec<usize>,
+    propagated: usize,
+    // Variables that were in recent conflicts are picked first, with the value they had last
+    activity: Vec<f64>,
+    activity_increment: f64,
+    heap: BinaryHeap<(u64, usize)>,
+    phases: Vec<bool>,
+    seen: Vec<bool>,
+}
+
+impl Solver {
+    fn value(&self, literal: i32) -> i8 {
+        let value = self.values[variable(literal)];
+        if literal < 0 {
+            -value
+        } else {
+            value
+        }
+    }
+
+    fn level(&self) -> usize {
+        self.trail_starts.len()
+    }
+
+    fn assign(&mut self, literal: i32, reason: Option<usize>) {
+        let variable = variable(literal);
+        self.values[variable] = if literal < 0 { -1 } else { 1 };
+        self.levels[variable] = self.level();
+        self.reasons[variable] = reason;
+        self.trail.push(literal);
+    }
+
+    fn watch(&mut self, clause: usize) {
+        let (first, second) = (self.clauses[clause][0], self.clauses[clause][1]);
+        self.watches[code(first)].push(clause);
+        self.watches[code(second)].push(clause);
+    }
+
+    // Assign every literal that is the last one left in a clause, returns the clause that became false (if any)
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let false_literal = -self.trail[self.propagated];
+            self.propagated += 1;
+
+            let mut watching = std::mem::take(&mut self.watches[code(false_literal)]);
+            let mut i = 0;
+            let mut conflict = None;
+            while i < watching.len() {
+                let clause = watching[i];
+                // Keep the false literal second, so the first one is the one that may be implied
+                if self.clauses[clause][0] == false_literal {
+                    self.clauses[clause].swap(0, 1);
+                }
+                let first = self.clauses[clause][0];
+                if self.value(first) == 1 {
+                    i += 1;
+                    continue;
+                }
+
+                // Watch another literal that is not false, if there is one
+                let replacement = (2..self.clauses[clause].len())
+                    .find(|k| self.value(self.clauses[clause][*k]) != -1);
+                if let Some(k) = replacement {
+                    self.clauses[clause].swap(1, k);
+                    self.watches[code(self.clauses[clause][1])].push(clause);
+                    watching.swap_remove(i);
+                    continue;
+                }
+
+                if self.value(first) == -1 {
+                    conflict = Some(clause);
+                    break;
+                }
+                self.assign(first, Some(clause));
+                i += 1;
+            }
+            self.watches[code(false_literal)] = watching;
+            if conflict.is_some() {
+                self.propagated = self.trail.len();
+                return conflict;
+            }
+        }
+        None
+    }
+
+    fn bump(&mut self, variable: usize) {
+        self.activity[variable] += self.activity_increment;
+        if self.activity[variable] > 1e100 {
+            for activity in self.activity.iter_mut() {
+                *activity *= 1e-100;
+            }
+            self.activity_increment *= 1e-100;
+            self.heap = (0..self.values.len())
+                .filter(|variable| self.values[*variable] == 0)
+                .map(|variable| (self.activity[variable].to_bits(), variable))
+                .collect();
+        }
+        if self.values[variable] == 0 {
+            self.heap
+                .push((self.activity[variable].to_bits(), variable));
+        }
+    }
+
+    // Go back from the conflict until only one literal of the current level is left, the learned clause says
+    // that literal must be the other way around; returns the clause and the level to jump back to
+    fn analyze(&mut self, conflict: usize) -> (Vec<i32>, usize) {
+        let mut learned = vec![0];
+        let mut open = 0;
+        let mut literal = 0;
+        let mut index = self.trail.len();
+        let mut clause = conflict;
+        loop {
+            // The first literal of a reason is the one it implied
+            let start = if literal == 0 { 0 } else { 1 };
+            for k in start..self.clauses[clause].len() {
+                let other = self.clauses[clause][k];
+                let variable = variable(other);
+                if self.seen[variable] || self.levels[variable] == 0 {
+                    continue;
+                }
+                self.seen[variable] = true;
+                self.bump(variable);
+                if self.levels[variable] == self.level() {
+                    open += 1;
+                } else {
+                    learned.push(other);
+                }
+            }
+
+            loop {
+                index -= 1;
+                if self.seen[variable(self.trail[index])] {
+                    break;
+                }
+            }
+            literal = self.trail[index];
+            self.seen[variable(literal)] = false;
+            open -= 1;
+            if open == 0 {
+                break;
+            }
+            clause = self.reasons[variable(literal)].unwrap();
+        }
+        learned[0] = -literal;
+        for other in learned.iter() {
+            self.seen[variable(*other)] = false;
+        }
+
+        // The literal of the highest level after the first one is watched too, and decides how far to jump back
+        let mut level = 0;
+        for k in 1..learned.len() {
+            let other_level = self.levels[variable(learned[k])];
+            if other_level > level {
+                level = other_level;
+                learned.swap(1, k);
+            }
+        }
+        (learned, level)
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if self.level() <= level {
+            return;
+        }
+        let start = self.trail_starts[level];
+        for literal in self.trail.drain(start..) {
+            let variable = variable(literal);
+            self.phases[variable] = literal > 0;
+            self.values[variable] = 0;
+            self.reasons[variable] = None;
+            self.heap
+                .push((self.activity[variable].to_bits(), variable));
+        }
+        self.trail_starts.truncate(level);
+        self.propagated = self.trail.len();
+    }
+
+    fn pick(&mut self) -> Option<usize> {
+        while let Some((_, variable)) = self.heap.pop() {
+            if self.values[variable] == 0 {
+                return Some(variable);
+            }
+        }
+        None
+    }
+}
+
+// A value for every variable (index 0 is variable 1) that makes every clause true, or None if there is none
+pub fn solve(cnf: &Cnf) -> Option<Vec<bool>> {
+    let variables = cnf.variables;
+    let mut solver = Solver {
+        clauses: Vec::new(),
+        watches: vec![Vec::new(); 2 * variables],
+        values: vec![0; variables],
+        levels: vec![0; variables],
+        reasons: vec![None; variables],
+        trail: Vec::new(),
+        trail_starts: Vec::new(),
+        propagated: 0,
+        activity: vec![0.0; variables],
+        activity_increment: 1.0,
+        heap: (0..variables).map(|variable| (0, variable)).collect(),
+        phases: vec![false; variables],
+        seen: vec![false; variables],
+    };
+
+    for clause in cnf.clauses.iter() {
+        // Repeated literals only need to be there once, and a clause with both x and -x is always true
+        let mut clause = clause.clone();
+        clause.sort_unstable();
+        clause.dedup();
+        if clause
+            .iter()
+            .any(|literal| clause.binary_search(&-literal).is_ok())
+        {
+            continue;
+        }
+        match clause.len() {
+            0 => return None,
+            1 => match solver.value(clause[0]) {
+                -1 => return None,
+                0 => solver.assign(clause[0], None),
+                _ => {}
+            },
+            _ => {
+                solver.clauses.push(clause);
+                solver.watch(solver.clauses.len() - 1);
+            }
+        }
+    }
+
+    let mut conflicts = 0;
+    let mut restarts = 0;
+    let mut restart_at = RESTART_INTERVAL;
+    loop {
+        if let Some(conflict) = solver.propagate() {
+            if solver.level() == 0 {
+                return None;
+            }
+            let (learned, level) = solver.analyze(conflict);
+            solver.backtrack(level);
+            if learned.len() == 1 {
+                solver.assign(learned[0], None);
+            } else {
+                solver.clauses.push(learned);
+                let clause = solver.clauses.len() - 1;
+                solver.watch(clause);
+                solver.assign(solver.clauses[clause][0], Some(clause));
+            }
+            solver.activity_increment /= ACTIVITY_DECAY;
+            conflicts += 1;
+            continue;
+        }
+
+        if conflicts >= restart_at {
+            restarts += 1;
+            restart_at = conflicts + RESTART_INTERVAL * luby(restarts);
+            solver.backtrack(0);
+            continue;
+        }
+
+        let Some(variable) = solver.pick() else {
+            return Some(solver.values.iter().map(|value| *value > 0).collect());
+        };
+        solver.trail_starts.push(solver.trail.len());
+        let literal = variable as i32 + 1;
+        let literal = if solver.phases[variable] {
+            literal
+        } else {
+            -literal
+        };
+        solver.assign(literal, None);
+    }
+}
@@ -1,275 +1,1394 @@
-use color_eyre::owo_colors::OwoColorize;
+use std::borrow::Cow;
 
-struct Node {
-    character: char,
-    is_word: bool,
-    children: Vec<Box<Node>>,
+use crate::cache::Table;
+use crate::letters::{self, LetterSet, Signature};
+
+// A node in the wordlist tree, the children of every node are stored next to each other
+// The layout is fixed (16 bytes, the fields in this order) so the nodes can be used straight from the cache file
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct Node {
+    pub(crate) character: char,
+    pub(crate) first_child: u32,
+    pub(crate) child_count: u32,
+    pub(crate) is_word: bool,
+}
+
+// The wordlist tree, stored breadth first in a single list with the root at index 0
+pub struct Tree {
+    pub(crate) nodes: Table<Node>,
+    // For every length, how many words have that many characters
+    length_counts: Vec<usize>,
+    // Every letter that appears somewhere in the tree
+    pub(crate) letters: LetterSet,
+    // The signature of the word that ends at every node (empty for the other nodes),
+    // the signature of node i is signatures[signature_starts[i]..signature_starts[i + 1]]
+    pub(crate) signature_starts: Table<u32>,
+    pub(crate) signatures: Table<[u8; 2]>,
+}
+
+impl Tree {
+    pub(crate) fn from_nodes(
+        nodes: Table<Node>,
+        signature_starts: Table<u32>,
+        signatures: Table<[u8; 2]>,
+    ) -> Tree {
+        // Children always come after their parent, so the depth of every node is known before its children are reached
+        let mut depths = vec![0; nodes.len()];
+        let mut length_counts = Vec::new();
+        let mut letters = LetterSet::new();
+        for (index, node) in nodes.iter().enumerate() {
+            let depth = depths[index];
+            if index > 0 {
+                letters.insert(node.character);
+            }
+            if node.is_word {
+                if length_counts.len() <= depth {
+                    length_counts.resize(depth + 1, 0);
+                }
+                length_counts[depth] += 1;
+            }
+            let first_child = node.first_child as usize;
+            for child_depth in
+                depths[first_child..first_child + node.child_count as usize].iter_mut()
+            {
+                *child_depth = depth + 1;
+            }
+        }
+        Tree {
+            nodes,
+            length_counts,
+            letters,
+            signature_starts,
+            signatures,
+        }
+    }
+
+    // How often every letter appears in the word that ends at the node
+    pub fn signature(&self, index: usize) -> &Signature {
+        &self.signatures
+            [self.signature_starts[index] as usize..self.signature_starts[index + 1] as usize]
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.nodes[0]
+    }
+
+    pub fn letters(&self) -> LetterSet {
+        self.letters
+    }
+
+    pub fn children(&self, node: &Node) -> &[Node] {
+        let first_child = node.first_child as usize;
+        &self.nodes[first_child..first_child + node.child_count as usize]
+    }
 }
 
-fn construct_tree(input: Vec<&str>) -> Node {
-    let mut root = Node {
+pub fn construct_tree(input: Vec<impl AsRef<str>>) -> Tree {
+    // Sorted words that share a prefix are next to each other, so the children of a node can be found by grouping
+    let mut words: Vec<Vec<char>> = input
+        .iter()
+        .map(|word| word.as_ref().chars().collect())
+        .collect();
+    words.sort_unstable();
+    words.dedup();
+
+    let mut nodes = vec![Node {
         character: ' ',
         is_word: false,
-        children: Vec::new(),
-    };
+        first_child: 0,
+        child_count: 0,
+    }];
 
-    for line in input {
-        let mut current_node = &mut root;
-        for character in line.chars() {
-            let child_index = current_node
-                .children
-                .iter()
-                .position(|n| n.character == character);
-
-            match child_index {
-                Some(index) => current_node = current_node.children[index].as_mut(),
-                None => {
-                    let new_node = Node {
-                        character,
-                        is_word: false,
-                        children: Vec::new(),
-                    };
-                    current_node.children.push(Box::new(new_node));
-                    current_node = current_node.children.last_mut().unwrap();
-                }
+    // Go breadth first over (node, range of words that start with the node's prefix, depth of the node)
+    // The nodes come out of the queue in order, so their signatures are added in order too
+    let mut signature_starts: Vec<u32> = Vec::new();
+    let mut signatures: Vec<[u8; 2]> = Vec::new();
+    let mut queue = std::collections::VecDeque::from([(0, 0..words.len(), 0)]);
+    while let Some((index, range, depth)) = queue.pop_front() {
+        let mut start = range.start;
+
+        // A word that ends at this node sorts before the longer words with the same prefix
+        signature_starts.push(signatures.len() as u32);
+        if start < range.end && words[start].len() == depth {
+            nodes[index].is_word = true;
+            letters::push_signature(&mut signatures, &words[start]);
+            start += 1;
+        }
+
+        nodes[index].first_child = nodes.len() as u32;
+        while start < range.end {
+            let character = words[start][depth];
+            let mut end = start + 1;
+            while end < range.end && words[end][depth] == character {
+                end += 1;
             }
+
+            queue.push_back((nodes.len(), start..end, depth + 1));
+            nodes.push(Node {
+                character,
+                is_word: false,
+                first_child: 0,
+                child_count: 0,
+            });
+            nodes[index].child_count += 1;
+            start = end;
         }
-        current_node.is_word = true;
     }
 
-    root
+    signature_starts.push(signatures.len() as u32);
+
+    Tree::from_nodes(nodes.into(), signature_starts.into(), signatures.into())
 }
 
-fn get_number_of_characters_per_number_count(input: &str) -> [u8; 10] {
+pub fn get_number_of_characters_per_number_count(input: &str) -> Vec<u32> {
+    number_letter_counts(&parse_input(input))
+}
+
+fn number_letter_counts(input_numbers: &[Vec<u32>]) -> Vec<u32> {
     // Count how many times each number appears in the input
-    let mut number_counts: [u8; 10] = [0; 10];
-    for character in input.chars() {
-        // If space, skip
-        if character == ' ' {
-            continue;
-        }
-        let number = character.to_digit(10).unwrap() as usize;
-        number_counts[number] += 1;
+    let largest = input_numbers.iter().flatten().copied().max().unwrap_or(0) as usize;
+    let mut number_counts: Vec<u32> = vec![0; (largest + 1).max(10)];
+    for number in input_numbers.iter().flatten() {
+        number_counts[*number as usize] += 1;
     }
 
     // Get the number of letters for each number (if the number 3 appears 9 times, then there the number 3 can correspond to 3 different letters)
-    let mut number_letter_counts: [u8; 10] = [0; 10];
+    let mut number_letter_counts: Vec<u32> = vec![0; number_counts.len()];
     for (i, number_count) in number_counts.iter().enumerate() {
         if i == 0 {
             continue;
         }
-        number_letter_counts[i] = *number_count / (i as u8);
+        number_letter_counts[i] = *number_count / (i as u32);
     }
 
     number_letter_counts
 }
 
-fn is_valid(
-    solution: Vec<Vec<char>>,
-    input_numbers: Vec<Vec<u8>>,
-    wordlist_tree: &mut Node,
-    number_letter_counts: [u8; 10],
-) -> bool {
-    // Check if the last word up until current character is valid (i.e. the characters are nodes in the wordlist_tree)
-    let mut last_word = String::new();
-    for word in solution.iter() {
-        // Check if the current word is the last word
-        if word == solution.last().unwrap() {
-            // If the current word is the last word, then only check up until the current character
-            for (i, character) in word.iter().enumerate() {
-                if i == solution.last().unwrap().len() - 1 {
-                    break;
+// The numbers of every cipher word, and where the word is in the input
+// A number of 10 or more is written between brackets, like 2[12]1 for a word with the numbers 2, 12 and 1
+fn cipher_words(input: &str) -> Vec<(std::ops::Range<usize>, Vec<u32>)> {
+    cipher_words_and_oversized(input).0
+}
+
+// A number appears as many times as it says, so it can't be larger than the number of numbers in the input
+// Larger ones are left out (with their brackets) and returned separately, so they can be reported as malformed,
+// which also keeps the lists indexed by number small
+fn cipher_words_and_oversized(input: &str) -> CipherWords {
+    let total: usize = cipher_words_up_to(input, u32::MAX)
+        .0
+        .iter()
+        .map(|(_, numbers)| numbers.len())
+        .sum();
+    cipher_words_up_to(input, total as u32)
+}
+
+// The cipher words (where they are in the input, and their numbers), and where the numbers that are too large are
+type CipherWords = (
+    Vec<(std::ops::Range<usize>, Vec<u32>)>,
+    Vec<std::ops::Range<usize>>,
+);
+
+fn cipher_words_up_to(input: &str, largest: u32) -> CipherWords {
+    let mut words: Vec<(std::ops::Range<usize>, Vec<u32>)> = Vec::new();
+    let mut oversized: Vec<std::ops::Range<usize>> = Vec::new();
+    let mut current: Option<(std::ops::Range<usize>, Vec<u32>)> = None;
+    let mut characters = input.char_indices().peekable();
+    while let Some((index, character)) = characters.next() {
+        // A single digit, or a whole number between brackets
+        let number = if let Some(digit) = character.to_digit(10) {
+            Some((digit, index + 1))
+        } else if character == '[' {
+            let rest = &input[index + 1..];
+            let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+            let end = index + digits + 2;
+            match rest[..digits].parse::<u32>() {
+                _ if digits == 0 || !rest[digits..].starts_with(']') => None,
+                Ok(number) if number <= largest => Some((number, end)),
+                // Too large (or too large for a u32), the number is left out instead of being read as separate digits
+                _ => {
+                    oversized.push(index..end);
+                    while characters.next_if(|(i, _)| *i < end).is_some() {}
+                    None
                 }
-                last_word.push(*character);
             }
         } else {
-            // If the current word is not the last word, then check the entire word
-            for character in word.iter() {
-                last_word.push(*character);
+            None
+        };
+
+        // Anything that is not a number (spaces, punctuation) ends the word
+        match number {
+            Some((number, end)) => {
+                let word = current.get_or_insert((index..end, Vec::new()));
+                word.0.end = end;
+                word.1.push(number);
+                while characters.next_if(|(i, _)| *i < end).is_some() {}
             }
+            None => words.extend(current.take()),
         }
     }
+    words.extend(current);
+    (words, oversized)
+}
 
-    println!("Last word: {}", last_word.bright_green());
+// Write the numbers of a cipher word the way they are in the input, with the numbers of 10 or more between brackets
+pub fn format_numbers(numbers: &[u32]) -> String {
+    numbers
+        .iter()
+        .map(|number| {
+            if *number < 10 {
+                number.to_string()
+            } else {
+                format!("[{}]", number)
+            }
+        })
+        .collect()
+}
 
-    // Traverse the tree and check if a valid node exists for the last character
-    let mut current_node = wordlist_tree;
-    for character in last_word.chars() {
-        if character == ' ' {
-            break;
+// Input looks like this: 775237 8417415532 4831582
+// Seperate the input into a vector of vectors of numbers, anything that is not a number (spaces, punctuation) separates the words
+pub fn parse_input(input: &str) -> Vec<Vec<u32>> {
+    cipher_words(input)
+        .into_iter()
+        .map(|(_, numbers)| numbers)
+        .collect()
+}
+
+// The bracketed numbers that are larger than the number of numbers in the input, as they are written in it
+// They are not part of any cipher word, the ciphertext is malformed
+pub fn oversized_numbers(input: &str) -> Vec<&str> {
+    cipher_words_and_oversized(input)
+        .1
+        .into_iter()
+        .map(|range| &input[range])
+        .collect()
+}
+
+// Like parse_input, but a bracketed number is never too large (while fixing typos the number of numbers still changes)
+pub(crate) fn parse_input_without_limit(input: &str) -> Vec<Vec<u32>> {
+    cipher_words_up_to(input, u32::MAX)
+        .0
+        .into_iter()
+        .map(|(_, numbers)| numbers)
+        .collect()
+}
+
+// Put the decoded words back into the input, so the punctuation and line breaks stay where they were
+pub fn render(input: &str, solution: &[Vec<char>]) -> String {
+    let mut output = String::new();
+    let mut last_end = 0;
+    for (i, (range, _)) in cipher_words(input).into_iter().enumerate() {
+        output.push_str(&input[last_end..range.start]);
+
+        // Unknown letters are shown as dots, and numbers without a word are kept as they are
+        match solution.get(i) {
+            Some(word) => output.extend(word.iter().map(|c| if *c == ' ' { '.' } else { *c })),
+            None => output.push_str(&input[range.clone()]),
+        }
+        last_end = range.end;
+    }
+    output.push_str(&input[last_end..]);
+    output
+}
+
+// Remove all the words from the wordlist with numbers and non-letter characters (dashes, apostrophes, etc.),
+// and with letters that don't fit in a letter set (only a-z and the accented letters à-ÿ do, in either case)
+// Empty lines are skipped too, merged wordlists have them where a file ended with a line break
+pub(crate) fn wordlist_lines(wordlist: &str) -> impl Iterator<Item = &str> {
+    wordlist.lines().filter(|word| {
+        !word.is_empty()
+            && word
+                .chars()
+                .flat_map(char::to_lowercase)
+                .all(|c| letters::index(c).is_some())
+    })
+}
+
+// The words of the wordlist that can be used, lowercased (names like Anna are written the same way as other words
+// in the puzzle), words that are already lowercase are not copied
+pub fn filter_wordlist(wordlist: &str) -> Vec<Cow<'_, str>> {
+    wordlist_lines(wordlist).map(lowercase).collect()
+}
+
+pub(crate) fn lowercase(word: &str) -> Cow<'_, str> {
+    if word.chars().any(char::is_uppercase) {
+        Cow::Owned(word.to_lowercase())
+    } else {
+        Cow::Borrowed(word)
+    }
+}
+
+// Create an empty solution (a space for every unknown character) with the same shape as the input
+pub fn empty_solution(input_numbers: &[Vec<u32>]) -> Vec<Vec<char>> {
+    input_numbers
+        .iter()
+        .map(|word| vec![' '; word.len()])
+        .collect()
+}
+
+// Settings for the search that are not part of the puzzle itself
+#[derive(Clone, Debug)]
+pub struct Options {
+    // A cipher word may be up to this many words from the wordlist glued together (Dutch compounds like kerstboom)
+    pub max_components: usize,
+    // Up to this many cipher words don't have to be in the wordlist (names, new words), they only have to follow the numbers
+    pub max_unknown_words: usize,
+    // Which empty position the solver fills in next
+    pub order: Order,
+    // The letters the solver tries at every position, in this order
+    pub letter_order: Vec<char>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            max_components: 1,
+            max_unknown_words: 0,
+            order: Order::Fixed,
+            letter_order: ('a'..='z').collect(),
         }
-        let child_index = current_node
-            .children
+    }
+}
+
+// The letters from most to least common in the wordlist, so likely decodings are found sooner (e n a t i r o ... for Dutch)
+// Accented letters that appear in the wordlist are in the order too, letters of a-z that don't appear come last
+pub fn letter_order_from_wordlist(words: &[impl AsRef<str>]) -> Vec<char> {
+    let mut counts = [0usize; letters::CAPACITY];
+    for index in words
+        .iter()
+        .flat_map(|word| word.as_ref().chars())
+        .filter_map(letters::index)
+    {
+        counts[index] += 1;
+    }
+    let mut order: Vec<(char, usize)> = (0..letters::CAPACITY)
+        .filter(|index| *index < 26 || counts[*index] > 0)
+        .map(|index| (letters::letter(index), counts[index]))
+        .collect();
+    // Sorting is stable, so letters that are just as common stay in alphabetical order
+    order.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    order.into_iter().map(|(letter, _)| letter).collect()
+}
+
+// A letter order given by hand, the letters that are left out are tried last in alphabetical order
+pub fn letter_order(letters: &str) -> Vec<char> {
+    let mut order: Vec<char> = Vec::new();
+    for letter in letters
+        .chars()
+        .flat_map(char::to_lowercase)
+        .chain('a'..='z')
+    {
+        if letters::index(letter).is_some() && !order.contains(&letter) {
+            order.push(letter);
+        }
+    }
+    order
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Order {
+    // Word by word, and every word from left to right
+    Fixed,
+    // First the positions under a number that has all its letters already, then the word with the fewest candidates left
+    MostConstrained,
+}
+
+impl Order {
+    pub fn parse(name: &str) -> Option<Order> {
+        match name {
+            "fixed" => Some(Order::Fixed),
+            "most-constrained" => Some(Order::MostConstrained),
+            _ => None,
+        }
+    }
+}
+
+impl Tree {
+    // Check if the word is in the tree
+    pub fn contains(&self, word: &str) -> bool {
+        matches_pattern(self, self.root(), &word.chars().collect::<Vec<char>>(), 1)
+    }
+
+    // The signature of a word in the tree, None if it is not in the tree
+    pub fn word_signature(&self, word: &str) -> Option<&Signature> {
+        let mut index = 0;
+        for character in word.chars() {
+            let node = &self.nodes[index];
+            index = (node.first_child..node.first_child + node.child_count)
+                .map(|child| child as usize)
+                .find(|child| self.nodes[*child].character == character)?;
+        }
+        self.nodes[index].is_word.then(|| self.signature(index))
+    }
+
+    // Split the word into as few words from the tree as possible, None if it can't be split at all
+    pub fn segment(&self, word: &str) -> Option<Vec<String>> {
+        let characters: Vec<char> = word.chars().collect();
+
+        // For every position: the fewest words that make up everything before it, and where the last of them starts
+        let mut best: Vec<Option<(usize, usize)>> = vec![None; characters.len() + 1];
+        best[0] = Some((0, 0));
+        for start in 0..characters.len() {
+            let Some((components, _)) = best[start] else {
+                continue;
+            };
+            // Walk down the tree from this position, every word on the way is a possible next component
+            let mut node = self.root();
+            for (end, character) in characters.iter().enumerate().skip(start) {
+                match self
+                    .children(node)
+                    .iter()
+                    .find(|child| child.character == *character)
+                {
+                    Some(child) => node = child,
+                    None => break,
+                }
+                if node.is_word && best[end + 1].is_none_or(|(other, _)| components + 1 < other) {
+                    best[end + 1] = Some((components + 1, start));
+                }
+            }
+        }
+
+        // Follow the starts back from the end of the word
+        let mut components = Vec::new();
+        let mut end = characters.len();
+        while end > 0 {
+            let (_, start) = best[end]?;
+            components.push(characters[start..end].iter().collect());
+            end = start;
+        }
+        components.reverse();
+        (!components.is_empty()).then_some(components)
+    }
+}
+
+// Check if some word in the tree (or a compound of up to that many words) has this many characters
+fn has_length(tree: &Tree, length: usize, components: usize) -> bool {
+    // The lengths that can be made with the words used so far, adding one more word every round
+    let mut reachable = vec![false; length + 1];
+    reachable[0] = true;
+    for _ in 0..components {
+        let mut next = reachable.clone();
+        for (start, _) in reachable.iter().enumerate().filter(|(_, r)| **r) {
+            for (word_length, _) in tree
+                .length_counts
+                .iter()
+                .enumerate()
+                .skip(1)
+                .filter(|(word_length, count)| **count > 0 && start + word_length <= length)
+            {
+                next[start + word_length] = true;
+            }
+        }
+        // Stop as soon as the length can be made, or when another word doesn't make anything new
+        if next[length] || next == reachable {
+            return next[length];
+        }
+        reachable = next;
+    }
+    reachable[length]
+}
+
+// Count the words in the tree that match the pattern (without compounds), stopping once the limit is reached
+fn count_matches(tree: &Tree, node: &Node, pattern: &[char], limit: usize) -> usize {
+    match pattern.split_first() {
+        None => node.is_word as usize,
+        Some((character, rest)) => {
+            let mut count = 0;
+            for child in tree.children(node) {
+                if *character == ' ' || child.character == *character {
+                    count += count_matches(tree, child, rest, limit - count);
+                    if count >= limit {
+                        break;
+                    }
+                }
+            }
+            count
+        }
+    }
+}
+
+// Like matches_pattern, but the word must also fit the letters placed so far: a letter at an empty position must be able
+// to go under the number there, and the letters the word adds (its signature, minus the letters that are filled in)
+// may not push a letter past its number
+// That is a single look at the signature of every word that matches, the parts of a compound are only matched on their letters
+fn fits_word(
+    tree: &Tree,
+    index: usize,
+    pattern: &[char],
+    numbers: &[u32],
+    components: usize,
+    filled_in: &[u8; letters::CAPACITY],
+    counts: &LetterCounts,
+) -> bool {
+    let node = &tree.nodes[index];
+    if node.is_word && components > 1 && !pattern.is_empty() {
+        let rest_matches = if pattern.iter().all(|c| *c == ' ') {
+            has_length(tree, pattern.len(), components - 1)
+        } else {
+            matches_pattern(tree, tree.root(), pattern, components - 1)
+        };
+        if rest_matches {
+            return true;
+        }
+    }
+    let children = node.first_child as usize..(node.first_child + node.child_count) as usize;
+    match pattern.split_first() {
+        None => {
+            node.is_word
+                && counts
+                    .signature_conflict(tree.signature(index), filled_in)
+                    .is_none()
+        }
+        Some((' ', rest)) => children.into_iter().any(|child| {
+            counts.fits(tree.nodes[child].character, numbers[0])
+                && fits_word(
+                    tree,
+                    child,
+                    rest,
+                    &numbers[1..],
+                    components,
+                    filled_in,
+                    counts,
+                )
+        }),
+        Some((character, rest)) => children
+            .into_iter()
+            .find(|child| tree.nodes[*child].character == *character)
+            .is_some_and(|child| {
+                fits_word(
+                    tree,
+                    child,
+                    rest,
+                    &numbers[1..],
+                    components,
+                    filled_in,
+                    counts,
+                )
+            }),
+    }
+}
+
+// Check if some word in the tree matches the pattern, where a space matches any character
+// With more than one component left, a word may end halfway and the rest of the pattern start again at the root
+fn matches_pattern(tree: &Tree, node: &Node, pattern: &[char], components: usize) -> bool {
+    if node.is_word && components > 1 && !pattern.is_empty() {
+        // When nothing of the rest is filled in yet, only its length matters
+        let rest_matches = if pattern.iter().all(|c| *c == ' ') {
+            has_length(tree, pattern.len(), components - 1)
+        } else {
+            matches_pattern(tree, tree.root(), pattern, components - 1)
+        };
+        if rest_matches {
+            return true;
+        }
+    }
+    match pattern.split_first() {
+        None => node.is_word,
+        Some((' ', rest)) => tree
+            .children(node)
             .iter()
-            .position(|n| n.character == character);
-
-        match child_index {
-            Some(index) => current_node = current_node.children[index].as_mut(),
-            None => {
-                println!(
-                    "Invalid solution: {} is not a valid word",
-                    last_word.bright_red()
-                );
-                return false;
+            .any(|child| matches_pattern(tree, child, rest, components)),
+        Some((character, rest)) => tree
+            .children(node)
+            .iter()
+            .find(|child| child.character == *character)
+            .is_some_and(|child| matches_pattern(tree, child, rest, components)),
+    }
+}
+
+// The constraint that rules out a (partial) solution or a candidate word
+#[derive(Clone, Debug, PartialEq)]
+pub enum Conflict {
+    // No word in the wordlist fits the letters filled in so far and the letter counts (the trie walk found no match)
+    NotInWordlist {
+        word: usize,
+        pattern: String,
+    },
+    // A candidate word does not match a letter that is already filled in
+    FixedLetter {
+        position: usize,
+        letter: char,
+    },
+    // The letter is already under another number in an earlier word
+    Propagation {
+        letter: char,
+        number: u32,
+        word: usize,
+        other_number: u32,
+    },
+    // The letter appears more often than the number it is under
+    LetterCountExceeded {
+        letter: char,
+        number: u32,
+    },
+    // A number has more different letters than it can correspond to
+    BudgetExceeded {
+        number: u32,
+        letters: usize,
+        budget: u32,
+    },
+    // With everything filled in, the letter does not appear exactly as often as its number
+    LetterCountMismatch {
+        letter: char,
+        count: u32,
+        number: u32,
+    },
+}
+
+impl Conflict {
+    // Short name of the constraint, used to group explanations
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Conflict::NotInWordlist { .. } => "not in wordlist",
+            Conflict::FixedLetter { .. } => "fixed letter",
+            Conflict::Propagation { .. } => "propagation",
+            Conflict::LetterCountExceeded { .. } => "letter count exceeded",
+            Conflict::BudgetExceeded { .. } => "number budget exceeded",
+            Conflict::LetterCountMismatch { .. } => "letter count mismatch",
+        }
+    }
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Conflict::NotInWordlist { word, pattern } => {
+                write!(f, "Word {} ({}) is not in the wordlist", word + 1, pattern)
             }
+            Conflict::FixedLetter { position, letter } => {
+                write!(f, "Position {} is already {}", position + 1, letter)
+            }
+            Conflict::Propagation {
+                letter,
+                number,
+                word,
+                other_number,
+            } => write!(
+                f,
+                "Letter {} is under number {} in word {}, not number {}",
+                letter,
+                other_number,
+                word + 1,
+                number
+            ),
+            Conflict::LetterCountExceeded { letter, number } => {
+                write!(f, "Letter {} appears more than {} times", letter, number)
+            }
+            Conflict::BudgetExceeded {
+                number,
+                letters,
+                budget,
+            } => write!(
+                f,
+                "Number {} has {} different letters but can only correspond to {}",
+                number, letters, budget
+            ),
+            Conflict::LetterCountMismatch {
+                letter,
+                count,
+                number,
+            } => write!(
+                f,
+                "Letter {} appears {} times but is under number {}",
+                letter, count, number
+            ),
         }
     }
+}
 
-    // Compare the input numbers and characters in the solution to find how many letters each number corresponds to
-    // If there is a larger number of unique letters than the number of letters the number corresponds to, then the solution is invalid
-    // Use the number_letter_counts and the input_numbers and the current solution to find the number of unique letters for each number
+// The letters filled in so far, counted once and then kept up to date while letters are placed and removed,
+// so a letter (or a whole candidate word) is checked against the frequency rules without going over the solution again
+pub struct LetterCounts {
+    // For every letter in used: the number it is under, how often it appears, and the word where it was first used
+    used: LetterSet,
+    letter_numbers: [(u32, u32, usize); letters::CAPACITY],
+    // The different letters under every number, and how many different letters every number can have
+    number_letters: Vec<LetterSet>,
+    number_letter_counts: Vec<u32>,
+    // The largest number in the ciphertext, no letter can appear more often than that
+    max_number: u32,
+    // How many positions are not filled in yet
+    open: usize,
+}
 
-    // Create an empty vector of vectors of chars
-    let mut possible_letters: Vec<Vec<char>> = vec![Vec::new(); 10];
-    // Iterate over each number in the input
-    for (input_word, solution_word) in input_numbers.iter().zip(solution.iter()) {
-        // Iterate over each number in the input word
-        for (input_number, solution_character) in input_word.iter().zip(solution_word.iter()) {
-            // If the solution character is a space, skip
-            if *solution_character == ' ' {
-                continue;
+impl LetterCounts {
+    // Nothing filled in yet
+    pub fn new(input_numbers: &[Vec<u32>], number_letter_counts: &[u32]) -> LetterCounts {
+        let max_number = input_numbers.iter().flatten().copied().max().unwrap_or(0);
+        LetterCounts {
+            used: LetterSet::new(),
+            letter_numbers: [(0, 0, 0); letters::CAPACITY],
+            number_letters: vec![
+                LetterSet::new();
+                number_letter_counts.len().max(max_number as usize + 1)
+            ],
+            number_letter_counts: number_letter_counts.to_vec(),
+            max_number,
+            open: input_numbers.iter().map(|word| word.len()).sum(),
+        }
+    }
+
+    // Count every letter of the solution, and return the first rule it breaks (if any)
+    // A letter that breaks a rule is left out of the counts, the others are still counted
+    pub fn from_solution(
+        solution: &[Vec<char>],
+        input_numbers: &[Vec<u32>],
+        number_letter_counts: &[u32],
+    ) -> (LetterCounts, Option<Conflict>) {
+        let mut counts = LetterCounts::new(input_numbers, number_letter_counts);
+        let mut first_conflict = None;
+        for (i, (input_word, solution_word)) in
+            input_numbers.iter().zip(solution.iter()).enumerate()
+        {
+            for (input_number, solution_character) in input_word.iter().zip(solution_word.iter()) {
+                if *solution_character == ' ' {
+                    continue;
+                }
+                if let Some(conflict) = counts.add(*solution_character, *input_number, i) {
+                    first_conflict.get_or_insert(conflict);
+                }
+            }
+        }
+        (counts, first_conflict)
+    }
+
+    // Place a letter under a number in a word, unless that breaks a rule (then nothing changes)
+    // Every letter must always be under the same number, may not appear more often than that number,
+    // and a number can't have more different letters than it corresponds to
+    pub fn add(&mut self, letter: char, number: u32, word: usize) -> Option<Conflict> {
+        let Some(index) = letters::index(letter) else {
+            self.open -= 1;
+            return None;
+        };
+        let (letter_number, count, first_word) = self.letter_numbers[index];
+        if self.used.contains(letter) {
+            if letter_number != number {
+                return Some(Conflict::Propagation {
+                    letter,
+                    number,
+                    word: first_word,
+                    other_number: letter_number,
+                });
+            }
+            if count + 1 > number {
+                return Some(Conflict::LetterCountExceeded { letter, number });
             }
-            // If the solution character is not a space, add it to the possible letters for the current number
-            // ONLY if it is not already in the possible letters for the current number
-            if !possible_letters[*input_number as usize].contains(solution_character) {
-                possible_letters[*input_number as usize].push(*solution_character);
+            self.letter_numbers[index].1 += 1;
+        } else {
+            let number_letters = &mut self.number_letters[number as usize];
+            let budget = self
+                .number_letter_counts
+                .get(number as usize)
+                .copied()
+                .unwrap_or(0);
+            if number > 0 && number_letters.len() + 1 > budget as usize {
+                return Some(Conflict::BudgetExceeded {
+                    number,
+                    letters: number_letters.len() + 1,
+                    budget,
+                });
             }
+            number_letters.insert(letter);
+            self.used.insert(letter);
+            self.letter_numbers[index] = (number, 1, word);
         }
+        self.open -= 1;
+        None
     }
 
-    // Check if the number of unique letters for each number is less than or equal to the number of letters the number corresponds to
-    for (i, possible_letter) in possible_letters.iter().enumerate() {
-        if i == 0 {
+    // Take a placed letter away again, in the reverse order they were placed (so the first word of a letter stays right)
+    pub fn remove(&mut self, letter: char) {
+        self.open += 1;
+        let Some(index) = letters::index(letter) else {
+            return;
+        };
+        let (number, count, _) = &mut self.letter_numbers[index];
+        *count -= 1;
+        if *count == 0 {
+            self.used.remove(letter);
+            self.number_letters[*number as usize].remove(letter);
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.open == 0
+    }
+
+    // Check if a letter could go under a number, without placing it: a letter that is used already must be under
+    // the same number, and a new letter needs room among the different letters of the number
+    fn fits(&self, letter: char, number: u32) -> bool {
+        let Some(index) = letters::index(letter) else {
+            return true;
+        };
+        if self.used.contains(letter) {
+            return self.letter_numbers[index].0 == number;
+        }
+        let budget = self
+            .number_letter_counts
+            .get(number as usize)
+            .copied()
+            .unwrap_or(0);
+        number == 0 || self.number_letters[number as usize].len() < budget as usize
+    }
+
+    // A whole word on top of the letters placed so far, without going over its letters: a letter that is already
+    // under a number can only appear that often in total, and any other letter at most as often as the largest number
+    // The letters of the word that are already filled in (and so counted) are left out
+    pub fn signature_conflict(
+        &self,
+        signature: &Signature,
+        filled_in: &[u8; letters::CAPACITY],
+    ) -> Option<Conflict> {
+        signature.iter().find_map(|[index, count]| {
+            let index = *index as usize;
+            let added = count.saturating_sub(filled_in[index]) as u32;
+            if added == 0 {
+                return None;
+            }
+            let letter = letters::letter(index);
+            let (number, placed, _) = self.letter_numbers[index];
+            if self.used.contains(letter) {
+                (placed + added > number)
+                    .then_some(Conflict::LetterCountExceeded { letter, number })
+            } else {
+                (added > self.max_number).then_some(Conflict::LetterCountExceeded {
+                    letter,
+                    number: self.max_number,
+                })
+            }
+        })
+    }
+
+    // Once everything is filled in, every letter must appear exactly as often as its number says
+    pub fn mismatch(&self) -> Option<Conflict> {
+        self.used.iter().find_map(|letter| {
+            let (number, count, _) = self.letter_numbers[letters::index(letter).unwrap()];
+            (count != number).then_some(Conflict::LetterCountMismatch {
+                letter,
+                count,
+                number,
+            })
+        })
+    }
+}
+
+// Check if a word can still become a word from the wordlist (or a compound), given the letters placed so far
+fn word_fits(
+    word: &[char],
+    numbers: &[u32],
+    wordlist_tree: &Tree,
+    options: &Options,
+    counts: &LetterCounts,
+) -> bool {
+    // An empty word only needs a word with the same length, which is much cheaper to check than walking the tree
+    if word.iter().all(|c| *c == ' ') {
+        return has_length(wordlist_tree, word.len(), options.max_components);
+    }
+    fits_word(
+        wordlist_tree,
+        0,
+        word,
+        numbers,
+        options.max_components,
+        &letters::letter_counts(word),
+        counts,
+    )
+}
+
+fn not_in_wordlist(index: usize, word: &[char]) -> Conflict {
+    let pattern: String = word
+        .iter()
+        .map(|c| if *c == ' ' { '.' } else { *c })
+        .collect();
+    Conflict::NotInWordlist {
+        word: index,
+        pattern,
+    }
+}
+
+// Every word that has at least one letter filled in must still be able to become a word from the wordlist,
+// except for the few words that are allowed to be unknown
+fn word_conflict(
+    solution: &[Vec<char>],
+    input_numbers: &[Vec<u32>],
+    wordlist_tree: &Tree,
+    options: &Options,
+    counts: &LetterCounts,
+) -> Option<Conflict> {
+    let mut unknown_words = 0;
+    for (i, word) in solution.iter().enumerate() {
+        if !word_fits(word, &input_numbers[i], wordlist_tree, options, counts) {
+            unknown_words += 1;
+            if unknown_words > options.max_unknown_words {
+                return Some(not_in_wordlist(i, word));
+            }
+        }
+    }
+    None
+}
+
+// After placing a letter only that word has changed, so only that word is checked against the wordlist
+// When it doesn't fit and unknown words are allowed, the whole solution is checked to count the unknown words
+fn placed_word_conflict(
+    solution: &[Vec<char>],
+    input_numbers: &[Vec<u32>],
+    word: usize,
+    wordlist_tree: &Tree,
+    options: &Options,
+    counts: &LetterCounts,
+) -> Option<Conflict> {
+    if word_fits(
+        &solution[word],
+        &input_numbers[word],
+        wordlist_tree,
+        options,
+        counts,
+    ) {
+        None
+    } else if options.max_unknown_words == 0 {
+        Some(not_in_wordlist(word, &solution[word]))
+    } else {
+        word_conflict(solution, input_numbers, wordlist_tree, options, counts)
+    }
+}
+
+// Find the first reason the (partial) solution can not lead to a valid decoding, if any
+pub fn find_conflict(
+    solution: &[Vec<char>],
+    input_numbers: &[Vec<u32>],
+    wordlist_tree: &Tree,
+    number_letter_counts: &[u32],
+    options: &Options,
+) -> Option<Conflict> {
+    let (counts, letter_conflict) =
+        LetterCounts::from_solution(solution, input_numbers, number_letter_counts);
+    if let Some(conflict) = word_conflict(solution, input_numbers, wordlist_tree, options, &counts)
+    {
+        return Some(conflict);
+    }
+
+    match letter_conflict {
+        Some(conflict) => Some(conflict),
+        None if counts.is_complete() => counts.mismatch(),
+        None => None,
+    }
+}
+
+// The words of the solution that are not in the wordlist (the ones that only follow the numbers)
+pub fn unknown_words(
+    solution: &[Vec<char>],
+    wordlist_tree: &Tree,
+    options: &Options,
+) -> Vec<usize> {
+    solution
+        .iter()
+        .enumerate()
+        .filter(|(_, word)| {
+            !matches_pattern(
+                wordlist_tree,
+                wordlist_tree.root(),
+                word,
+                options.max_components,
+            )
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+// The words from the wordlist every word of a solution is made of: the word itself, or the fewest parts of a compound
+// (Tree::segment), and nothing for the words that are not in the wordlist
+pub fn split_words(solution: &[Vec<char>], wordlist_tree: &Tree) -> Vec<Vec<String>> {
+    solution
+        .iter()
+        .map(|word| {
+            wordlist_tree
+                .segment(&word.iter().collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+// Find the reason a word from the wordlist can not be used for the cipher word, given the letters filled in so far
+// The letters of the candidate at the empty positions are placed in the counts one by one, and taken away again after
+fn candidate_conflict(
+    candidate: &str,
+    word_index: usize,
+    numbers: &[u32],
+    pattern: &[char],
+    counts: &mut LetterCounts,
+) -> Option<Conflict> {
+    let mut placed: Vec<char> = Vec::new();
+    let mut conflict = None;
+    for (position, ((letter, number), fixed)) in candidate
+        .chars()
+        .zip(numbers.iter())
+        .zip(pattern.iter())
+        .enumerate()
+    {
+        // The letters filled in so far must match (and are already counted)
+        if *fixed != ' ' {
+            if *fixed != letter {
+                conflict = Some(Conflict::FixedLetter {
+                    position,
+                    letter: *fixed,
+                });
+                break;
+            }
             continue;
         }
-        if possible_letter.len() > number_letter_counts[i] as usize {
-            println!("Invalid solution: Number of unique characters for number {} is greater than the number of letters the number corresponds to", i);
-            // Print the number of unique letters for each number
-            for (i, possible_letter) in possible_letters.iter().enumerate() {
-                println!("Number {}: {:?}", i, possible_letter);
+        // Every other letter must be under the same number as elsewhere, and may not appear more often than that number
+        conflict = counts.add(letter, *number, word_index);
+        if conflict.is_some() {
+            break;
+        }
+        placed.push(letter);
+    }
+    for letter in placed.iter().rev() {
+        counts.remove(*letter);
+    }
+    conflict
+}
+
+// Go over every word from the wordlist with the same length as the cipher word, and find why it can not be used (if it can't)
+// The counts are the letters filled in so far (LetterCounts::from_solution), they are the same afterwards
+pub fn explain_candidates<'a>(
+    solution: &[Vec<char>],
+    input_numbers: &[Vec<u32>],
+    counts: &mut LetterCounts,
+    wordlist: &'a [impl AsRef<str>],
+    word_index: usize,
+) -> Vec<(&'a str, Option<Conflict>)> {
+    let numbers = &input_numbers[word_index];
+    let pattern = &solution[word_index];
+    wordlist
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|word| word.chars().count() == numbers.len())
+        .map(|word| {
+            (
+                word,
+                candidate_conflict(word, word_index, numbers, pattern, counts),
+            )
+        })
+        .collect()
+}
+
+// Count the words from the wordlist that still fit the cipher word, given the letters filled in so far
+pub fn count_candidates(
+    solution: &[Vec<char>],
+    input_numbers: &[Vec<u32>],
+    counts: &mut LetterCounts,
+    wordlist: &[impl AsRef<str>],
+    word_index: usize,
+) -> usize {
+    explain_candidates(solution, input_numbers, counts, wordlist, word_index)
+        .iter()
+        .filter(|(_, conflict)| conflict.is_none())
+        .count()
+}
+
+// A single letter tried by the solver, and the conflict that ruled it out (if any)
+pub struct Step<'a> {
+    pub solution: &'a [Vec<char>],
+    pub word: usize,
+    pub character: usize,
+    pub conflict: Option<&'a Conflict>,
+}
+
+// A letter the solver ruled out at a position, and why
+pub struct Elimination {
+    pub word: usize,
+    pub character: usize,
+    pub letter: char,
+    pub conflict: Conflict,
+}
+
+// Counting candidates for the most constrained order stops here, more than this many is not constrained anyway
+const CANDIDATE_LIMIT: usize = 1000;
+
+// The empty position the solver fills in next, None when everything is filled in
+pub fn next_position(
+    solution: &[Vec<char>],
+    input_numbers: &[Vec<u32>],
+    wordlist_tree: &Tree,
+    number_letter_counts: &[u32],
+    options: &Options,
+    current_word: usize,
+    current_character: usize,
+) -> Option<(usize, usize)> {
+    match options.order {
+        // The first empty position from the current one on (positions filled in by the user are skipped)
+        Order::Fixed => (current_word..solution.len()).find_map(|word| {
+            let start = if word == current_word {
+                current_character
+            } else {
+                0
+            };
+            (start..solution[word].len())
+                .find(|character| solution[word][*character] == ' ')
+                .map(|character| (word, character))
+        }),
+        Order::MostConstrained => {
+            // The letters that are already under every number (spaces are not letters, so they are not added)
+            let mut number_letters: Vec<LetterSet> =
+                vec![LetterSet::new(); number_letter_counts.len()];
+            for (numbers, word) in input_numbers.iter().zip(solution.iter()) {
+                for (number, letter) in numbers.iter().zip(word.iter()) {
+                    number_letters[*number as usize].insert(*letter);
+                }
             }
-            return false;
+
+            // Smallest (number still has letters to find, candidates left for the word) wins
+            let mut best: Option<((bool, usize), (usize, usize))> = None;
+            for (i, word) in solution.iter().enumerate() {
+                if word.iter().all(|c| *c != ' ') {
+                    continue;
+                }
+                let candidates = if word.iter().all(|c| *c == ' ') {
+                    wordlist_tree
+                        .length_counts
+                        .get(word.len())
+                        .copied()
+                        .unwrap_or(0)
+                } else {
+                    count_matches(wordlist_tree, wordlist_tree.root(), word, CANDIDATE_LIMIT)
+                };
+                // A word without candidates can only be a compound or an unknown word, those are not constrained by the wordlist
+                let candidates = if candidates == 0 {
+                    usize::MAX
+                } else {
+                    candidates
+                };
+
+                for (character, letter) in word.iter().enumerate() {
+                    if *letter != ' ' {
+                        continue;
+                    }
+                    let number = input_numbers[i][character] as usize;
+                    let open = number_letters[number].len() < number_letter_counts[number] as usize;
+                    let key = (open, candidates);
+                    if best.is_none_or(|(best_key, _)| key < best_key) {
+                        best = Some((key, (i, character)));
+                    }
+                }
+            }
+            best.map(|(_, position)| position)
         }
     }
+}
 
-    true
+// The letters to try at a position, in the order of the options
+// When everything before the position is filled in, only the letters that continue that prefix in the tree are tried
+// (with compounds or unknown words any letter can still fit, so then all of them are tried)
+pub fn candidate_letters(
+    solution: &[Vec<char>],
+    wordlist_tree: &Tree,
+    options: &Options,
+    word: usize,
+    character: usize,
+) -> Vec<char> {
+    // The letter order first, then the letters of the wordlist that are not in it (accented letters, if the order is a-z)
+    let all_letters = || {
+        let mut all_letters = options.letter_order.clone();
+        all_letters.extend(
+            wordlist_tree
+                .letters
+                .iter()
+                .filter(|letter| !options.letter_order.contains(letter)),
+        );
+        all_letters
+    };
+    if options.max_components > 1 || options.max_unknown_words > 0 {
+        return all_letters();
+    }
+
+    let mut node = wordlist_tree.root();
+    for letter in solution[word][..character].iter() {
+        if *letter == ' ' {
+            return all_letters();
+        }
+        match wordlist_tree
+            .children(node)
+            .iter()
+            .find(|child| child.character == *letter)
+        {
+            Some(child) => node = child,
+            None => return Vec::new(),
+        }
+    }
+
+    let children = wordlist_tree.children(node);
+    all_letters()
+        .into_iter()
+        .filter(|letter| children.iter().any(|child| child.character == *letter))
+        .collect()
 }
 
-fn backtrack_recursive(
+#[allow(clippy::too_many_arguments)]
+pub fn backtrack_recursive(
     solution: &mut Vec<Vec<char>>,
-    input_numbers: Vec<Vec<u8>>,
-    wordlist_tree: &mut Node,
-    number_letter_counts: [u8; 10],
+    input_numbers: &[Vec<u32>],
+    wordlist_tree: &Tree,
+    number_letter_counts: &[u32],
+    options: &Options,
     current_word: usize,
     current_character: usize,
+    progress: &mut dyn FnMut(&Step),
+    on_solution: &mut dyn FnMut(&[Vec<char>]) -> bool,
 ) -> bool {
-    // If previous word last character is a space, then the solution is invalid
-    if current_word > 0 && solution[current_word - 1][solution[current_word - 1].len() - 1] == ' ' {
+    // The letters that are already filled in are counted once, the search keeps the counts up to date
+    // (and only checks the words it places letters in, so the words that are filled in already are checked here)
+    let (mut counts, None) =
+        LetterCounts::from_solution(solution, input_numbers, number_letter_counts)
+    else {
+        return false;
+    };
+    if word_conflict(solution, input_numbers, wordlist_tree, options, &counts).is_some() {
         return false;
     }
+    search(
+        solution,
+        input_numbers,
+        wordlist_tree,
+        number_letter_counts,
+        &mut counts,
+        options,
+        current_word,
+        current_character,
+        progress,
+        on_solution,
+    )
+}
 
-    // If the current word is the last word, then the solution is valid
-    if current_word == solution.len() - 1 && current_character == solution[current_word].len() - 1 {
-        return true;
-    }
-
-    // If the current character is the last character in the current word, then move on to the next word
-    if current_character == solution[current_word].len() - 1 {
-        return backtrack_recursive(
-            solution,
-            input_numbers,
-            wordlist_tree,
-            number_letter_counts,
-            current_word + 1,
-            0,
-        );
-    }
+#[allow(clippy::too_many_arguments)]
+fn search(
+    solution: &mut Vec<Vec<char>>,
+    input_numbers: &[Vec<u32>],
+    wordlist_tree: &Tree,
+    number_letter_counts: &[u32],
+    counts: &mut LetterCounts,
+    options: &Options,
+    current_word: usize,
+    current_character: usize,
+    progress: &mut dyn FnMut(&Step),
+    on_solution: &mut dyn FnMut(&[Vec<char>]) -> bool,
+) -> bool {
+    // If every position is filled in, then the solution is valid (every letter was checked when it was placed)
+    // The caller decides if the search stops here or keeps looking for more solutions
+    let Some((current_word, current_character)) = next_position(
+        solution,
+        input_numbers,
+        wordlist_tree,
+        number_letter_counts,
+        options,
+        current_word,
+        current_character,
+    ) else {
+        return on_solution(solution);
+    };
 
-    // If the current character is not the last character in the current word, then try all possible letters for the current character
-    let possible_letters = "abcdefghijklmnopqrstuvwxyz".chars().collect::<Vec<char>>();
+    // Try the letters that can continue the word for the current character
+    let possible_letters = candidate_letters(
+        solution,
+        wordlist_tree,
+        options,
+        current_word,
+        current_character,
+    );
+    let number = input_numbers[current_word][current_character];
     for letter in possible_letters.iter() {
         solution[current_word][current_character] = *letter;
-        if is_valid(
-            solution.clone(),
-            input_numbers.clone(),
-            wordlist_tree,
-            number_letter_counts,
-        ) {
-            if backtrack_recursive(
+
+        // The frequency rules only need the running counts, and the word the letter is in is checked against
+        // the wordlist and the signatures of its words
+        let counted = counts.add(*letter, number, current_word);
+        let placed = counted.is_none();
+        let conflict = counted
+            .or_else(|| {
+                placed_word_conflict(
+                    solution,
+                    input_numbers,
+                    current_word,
+                    wordlist_tree,
+                    options,
+                    counts,
+                )
+            })
+            .or_else(|| {
+                if counts.is_complete() {
+                    counts.mismatch()
+                } else {
+                    None
+                }
+            });
+        progress(&Step {
+            solution,
+            word: current_word,
+            character: current_character,
+            conflict: conflict.as_ref(),
+        });
+        if conflict.is_none()
+            && search(
                 solution,
-                input_numbers.clone(),
+                input_numbers,
                 wordlist_tree,
                 number_letter_counts,
+                counts,
+                options,
                 current_word,
                 current_character + 1,
-            ) {
-                return true;
-            }
+                progress,
+                on_solution,
+            )
+        {
+            return true;
+        }
+        if placed {
+            counts.remove(*letter);
         }
     }
 
+    solution[current_word][current_character] = ' ';
     false
 }
 
-pub fn solve(input: &str, wordlist: &str) -> i64 {
-    // Remove all the words from the wordlist with numbers and non-letter characters (dashes, apostrophes, etc.)
-    let wordlist = wordlist
-        .lines()
-        .filter(|word| word.chars().all(|c| c.is_alphabetic()))
-        .collect::<Vec<&str>>();
+// Find the first decoding of the input, if there is one
+pub fn decode(input: &str, wordlist: &str) -> Option<Vec<Vec<char>>> {
+    let wordlist = filter_wordlist(wordlist);
 
     // Create a tree of the wordlist
-    let mut wordlist_tree = construct_tree(wordlist);
+    let wordlist_tree = construct_tree(wordlist);
 
     // Get number of different letters each number could correspond to
     let number_letter_counts = get_number_of_characters_per_number_count(input);
-    println!("Number letter counts: {:?}", number_letter_counts);
 
-    // Input looks like this: 775237 8417415532 4831582
-    // Seperate the input into a vector of vectors of numbers
-    let input_numbers: Vec<Vec<u8>> = input
-        .split_whitespace()
-        .map(|word| {
-            word.chars()
-                .map(|c| c.to_digit(10).unwrap() as u8)
-                .collect()
-        })
-        .collect();
+    let input_numbers = parse_input(input);
 
     // Use backtracking to find the solution
-    let mut solution: Vec<Vec<char>> = Vec::new();
-    for word in input_numbers.iter() {
-        let mut word_vec: Vec<char> = Vec::new();
-        for _ in word {
-            word_vec.push(' ');
-        }
-        solution.push(word_vec);
-    }
-
-    // solution[0][0] = 'a';
-    // solution[0][1] = 'a';
-    // solution[0][2] = 'z';
-    // solution[1][0] = 'z';
-    // println!("Solution: {:?}", solution);
-
-    // if is_valid(
-    //     solution.clone(),
-    //     input_numbers.clone(),
-    //     &mut wordlist_tree,
-    //     number_letter_counts,
-    // ) {
-    //     println!("Valid solution");
-    // }
-
-    backtrack_recursive(
+    let mut solution = empty_solution(&input_numbers);
+    let solved = backtrack_recursive(
         &mut solution,
-        input_numbers,
-        &mut wordlist_tree,
-        number_letter_counts,
+        &input_numbers,
+        &wordlist_tree,
+        &number_letter_counts,
+        &Options::default(),
         0,
         0,
+        &mut |_| {},
+        &mut |_| true,
+    );
+
+    solved.then_some(solution)
+}
+
+pub fn solve(input: &str, wordlist: &str) -> i64 {
+    println!(
+        "Number letter counts: {:?}",
+        get_number_of_characters_per_number_count(input)
     );
 
+    let solution = decode(input, wordlist);
     println!("Solution: {:?}", solution);
 
     0
@@ -0,0 +1,313 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::{cnf, old2solver, oldsolver, sat, solver};
+
+// When there is more than one wordlist, this many solutions are compared to find the best ranked one
+// (there can be far too many to compare them all, so hitting the limit is reported in the outcome)
+pub const RANKED_SOLUTIONS: usize = 100;
+
+// Every extra word a compound cipher word is split into costs this much when ranking solutions
+const COMPOUND_PENALTY: i32 = 1;
+
+// The cipher text, split into words, with the number of letters each number corresponds to
+pub struct Puzzle {
+    pub input_numbers: Vec<Vec<u32>>,
+    pub number_letter_counts: Vec<u32>,
+}
+
+impl Puzzle {
+    pub fn parse(input: &str) -> Puzzle {
+        Puzzle {
+            input_numbers: solver::parse_input(input),
+            number_letter_counts: solver::get_number_of_characters_per_number_count(input),
+        }
+    }
+
+    // Without word boundaries all the numbers are one long cipher word, which is split into words like a compound
+    pub fn parse_without_spaces(input: &str) -> Puzzle {
+        let numbers: String = solver::parse_input(input)
+            .iter()
+            .map(|word| solver::format_numbers(word))
+            .collect();
+        Puzzle::parse(&numbers)
+    }
+}
+
+// One of the wordlists the dictionary is made of, the priority says how much its words count when ranking solutions
+pub struct Source<'a> {
+    pub name: String,
+    pub priority: i32,
+    pub wordlist: &'a str,
+}
+
+// The filtered (and lowercased) wordlist and the tree built from it, shared by every strategy
+pub struct Dictionary<'a> {
+    pub words: Vec<Cow<'a, str>>,
+    pub tree: solver::Tree,
+    // Names and priorities of the wordlists, and which of them every word is in
+    pub sources: Vec<(String, i32)>,
+    pub word_sources: HashMap<Cow<'a, str>, Vec<usize>>,
+}
+
+impl<'a> Dictionary<'a> {
+    pub fn new(wordlist: &'a str) -> Dictionary<'a> {
+        let words = solver::filter_wordlist(wordlist);
+        let tree = solver::construct_tree(words.clone());
+        Dictionary {
+            words,
+            tree,
+            sources: Vec::new(),
+            word_sources: HashMap::new(),
+        }
+    }
+
+    // Remember which wordlist every word came from (the dictionary itself should be built from all of them together)
+    pub fn tag_sources(&mut self, sources: &[Source<'a>]) {
+        self.sources = sources
+            .iter()
+            .map(|source| (source.name.clone(), source.priority))
+            .collect();
+        self.word_sources.clear();
+        for (i, source) in sources.iter().enumerate() {
+            for word in solver::filter_wordlist(source.wordlist) {
+                let word_sources = self.word_sources.entry(word).or_default();
+                if !word_sources.contains(&i) {
+                    word_sources.push(i);
+                }
+            }
+        }
+    }
+
+    // Names of the wordlists the word is in
+    pub fn sources_of(&self, word: &str) -> Vec<&str> {
+        self.word_sources
+            .get(word)
+            .map(|sources| {
+                sources
+                    .iter()
+                    .map(|i| self.sources[*i].0.as_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Every word counts with the highest priority of the wordlists it is in (solver::split_words)
+    // A compound word counts every part, minus a penalty for every part after the first
+    pub fn score(&self, words: &[Vec<String>]) -> i32 {
+        words
+            .iter()
+            .map(|components| {
+                let priorities: i32 = components
+                    .iter()
+                    .map(|component| {
+                        self.word_sources
+                            .get(component.as_str())
+                            .and_then(|sources| sources.iter().map(|i| self.sources[*i].1).max())
+                            .unwrap_or(0)
+                    })
+                    .sum();
+                priorities - COMPOUND_PENALTY * components.len().saturating_sub(1) as i32
+            })
+            .sum()
+    }
+}
+
+// What a strategy found: a full decoding, and/or how many candidate words are left per cipher word
+pub struct Outcome {
+    pub solution: Option<Vec<Vec<char>>>,
+    // The words from the wordlist every decoded word is made of (see solver::split_words), empty without a decoding
+    pub words: Vec<Vec<String>>,
+    pub candidate_counts: Option<Vec<usize>>,
+    // Only the first RANKED_SOLUTIONS solutions were ranked, so a better one may have been missed
+    pub truncated: bool,
+}
+
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    fn solve(&self, puzzle: &Puzzle, dictionary: &Dictionary, options: &solver::Options)
+        -> Outcome;
+}
+
+// Backtracking over every character with the wordlist tree (solver.rs)
+pub struct Backtracking;
+
+impl Strategy for Backtracking {
+    fn name(&self) -> &'static str {
+        "solver"
+    }
+
+    fn description(&self) -> &'static str {
+        "Backtracking over every character, checked against the wordlist tree"
+    }
+
+    fn solve(
+        &self,
+        puzzle: &Puzzle,
+        dictionary: &Dictionary,
+        options: &solver::Options,
+    ) -> Outcome {
+        // With a single wordlist and no compounds the first solution is as good as any,
+        // otherwise rank them by the wordlists' priorities and the number of compound parts
+        let limit = if dictionary.sources.len() > 1 || options.max_components > 1 {
+            RANKED_SOLUTIONS
+        } else {
+            1
+        };
+
+        // Allow one more unknown word at a time, so the solutions with the fewest unknown words are found first
+        let mut solutions: Vec<Vec<Vec<char>>> = Vec::new();
+        for max_unknown_words in 0..=options.max_unknown_words {
+            let options = solver::Options {
+                max_unknown_words,
+                ..options.clone()
+            };
+            let mut solution = solver::empty_solution(&puzzle.input_numbers);
+            solver::backtrack_recursive(
+                &mut solution,
+                &puzzle.input_numbers,
+                &dictionary.tree,
+                &puzzle.number_letter_counts,
+                &options,
+                0,
+                0,
+                &mut |_| {},
+                &mut |solution| {
+                    solutions.push(solution.to_vec());
+                    solutions.len() == limit
+                },
+            );
+            if !solutions.is_empty() {
+                break;
+            }
+        }
+        let truncated = limit > 1 && solutions.len() == limit;
+
+        // The first of the best scoring solutions, with the words it is made of
+        let mut words: Vec<Vec<Vec<String>>> = solutions
+            .iter()
+            .map(|solution| solver::split_words(solution, &dictionary.tree))
+            .collect();
+        let best = words
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, words)| (dictionary.score(words), std::cmp::Reverse(*i)))
+            .map(|(i, _)| i);
+        Outcome {
+            solution: best.map(|i| solutions.swap_remove(i)),
+            words: best.map(|i| words.swap_remove(i)).unwrap_or_default(),
+            candidate_counts: None,
+            truncated,
+        }
+    }
+}
+
+// Encoding the candidate words and the letter counts as clauses, and handing them to a SAT solver (cnf.rs and sat.rs)
+// Only whole wordlist words are encoded, compounds and unknown words are up to the backtracking solver
+pub struct Satisfiability;
+
+impl Strategy for Satisfiability {
+    fn name(&self) -> &'static str {
+        "sat"
+    }
+
+    fn description(&self) -> &'static str {
+        "Encoding the candidates and letter counts as CNF, solved by the embedded SAT solver"
+    }
+
+    fn solve(&self, puzzle: &Puzzle, dictionary: &Dictionary, _: &solver::Options) -> Outcome {
+        let encoding = cnf::encode(puzzle, dictionary);
+        let solution = sat::solve(&encoding.cnf).map(|model| cnf::decode(&encoding, &model));
+        // Every word is a whole word from the wordlist
+        let words = solution
+            .iter()
+            .flatten()
+            .map(|word| vec![word.iter().collect()])
+            .collect();
+        Outcome {
+            solution,
+            words,
+            candidate_counts: None,
+            truncated: false,
+        }
+    }
+}
+
+// Repeatedly filtering the candidate words per cipher word by the possible letters per number (old2solver.rs)
+pub struct Filtering;
+
+impl Strategy for Filtering {
+    fn name(&self) -> &'static str {
+        "old2solver"
+    }
+
+    fn description(&self) -> &'static str {
+        "Filtering candidate words by the possible letters per number"
+    }
+
+    fn solve(&self, puzzle: &Puzzle, dictionary: &Dictionary, _: &solver::Options) -> Outcome {
+        let input_words = old2solver::filter_candidates(&puzzle.input_numbers, &dictionary.words);
+        Outcome {
+            solution: None,
+            words: Vec::new(),
+            truncated: false,
+            candidate_counts: Some(
+                input_words
+                    .iter()
+                    .map(|word| word.possible_words.len())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// The first attempt, filtering the 10 letter word by hand picked letters (oldsolver.rs)
+pub struct FirstAttempt;
+
+impl Strategy for FirstAttempt {
+    fn name(&self) -> &'static str {
+        "oldsolver"
+    }
+
+    fn description(&self) -> &'static str {
+        "First attempt, only filters candidates for the 10 letter word"
+    }
+
+    fn solve(&self, puzzle: &Puzzle, dictionary: &Dictionary, _: &solver::Options) -> Outcome {
+        let possible_words = oldsolver::filter_candidates(&puzzle.input_numbers, &dictionary.words);
+        Outcome {
+            solution: None,
+            words: Vec::new(),
+            truncated: false,
+            candidate_counts: Some(
+                puzzle
+                    .input_numbers
+                    .iter()
+                    .map(|word| {
+                        possible_words
+                            .iter()
+                            .filter(|possible_word| possible_word.len() == word.len())
+                            .count()
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// Every strategy, by name
+pub fn strategies() -> Vec<Box<dyn Strategy>> {
+    vec![
+        Box::new(Backtracking),
+        Box::new(Satisfiability),
+        Box::new(Filtering),
+        Box::new(FirstAttempt),
+    ]
+}
+
+pub fn find_strategy(name: &str) -> Option<Box<dyn Strategy>> {
+    strategies()
+        .into_iter()
+        .find(|strategy| strategy.name() == name)
+}
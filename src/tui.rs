@@ -0,0 +1,126 @@
+use color_eyre::owo_colors::{AnsiColors, OwoColorize};
+
+use crate::letters::LetterSet;
+use crate::solver;
+
+// Maximum width of a row of cipher words before wrapping to the next row
+const WIDTH: usize = 78;
+
+// Every number gets its own colour so the letters under it are easy to spot (numbers of 10 or more reuse them)
+const COLOURS: [AnsiColors; 10] = [
+    AnsiColors::White,
+    AnsiColors::BrightRed,
+    AnsiColors::BrightGreen,
+    AnsiColors::BrightYellow,
+    AnsiColors::BrightBlue,
+    AnsiColors::BrightMagenta,
+    AnsiColors::BrightCyan,
+    AnsiColors::Red,
+    AnsiColors::Green,
+    AnsiColors::Yellow,
+];
+
+// Clear the terminal and draw the cipher grid, the letter budget per number and the used/remaining letters
+// (of the letters the wordlist has)
+pub fn draw(
+    input_numbers: &[Vec<u32>],
+    solution: &[Vec<char>],
+    number_letter_counts: &[u32],
+    alphabet: LetterSet,
+    candidate_counts: Option<&[usize]>,
+    status: &str,
+) {
+    let mut screen = String::from("\x1b[2J\x1b[H");
+
+    // Split the words into rows that fit on the screen
+    let mut rows: Vec<Vec<usize>> = vec![Vec::new()];
+    let mut row_width = 0;
+    for (i, word) in input_numbers.iter().enumerate() {
+        let width = solver::format_numbers(word).len().max(6) + 2;
+        if row_width + width > WIDTH && !rows.last().unwrap().is_empty() {
+            rows.push(Vec::new());
+            row_width = 0;
+        }
+        rows.last_mut().unwrap().push(i);
+        row_width += width;
+    }
+
+    // Every row shows the numbers, the letters underneath and the remaining candidates
+    for row in rows.iter() {
+        let mut numbers_line = String::new();
+        let mut letters_line = String::new();
+        let mut candidates_line = String::new();
+        for &i in row.iter() {
+            let numbers_width = solver::format_numbers(&input_numbers[i]).len();
+            let width = numbers_width.max(6);
+            for (number, letter) in input_numbers[i].iter().zip(solution[i].iter()) {
+                // A number between brackets is wider than one letter, so the letter is padded to line up
+                let colour = COLOURS[*number as usize % COLOURS.len()];
+                let number = solver::format_numbers(&[*number]);
+                let letter = if *letter == ' ' { '.' } else { *letter };
+                let letter = format!("{:<width$}", letter, width = number.len());
+                numbers_line.push_str(&number.color(colour).to_string());
+                letters_line.push_str(&letter.color(colour).bold().to_string());
+            }
+            let padding = " ".repeat(width - numbers_width + 2);
+            numbers_line.push_str(&padding);
+            letters_line.push_str(&padding);
+
+            let candidates = match candidate_counts {
+                Some(counts) => format!("({})", counts[i]),
+                None => String::new(),
+            };
+            candidates_line.push_str(&format!("{:<width$}  ", candidates, width = width));
+        }
+        screen.push_str(&format!(
+            "{}\n{}\n{}\n\n",
+            numbers_line,
+            letters_line,
+            candidates_line.dimmed()
+        ));
+    }
+
+    // Collect which letters are currently under each number (unknown letters are spaces, which are not added)
+    let mut number_letters: Vec<LetterSet> = vec![LetterSet::new(); number_letter_counts.len()];
+    for (input_word, solution_word) in input_numbers.iter().zip(solution.iter()) {
+        for (number, letter) in input_word.iter().zip(solution_word.iter()) {
+            number_letters[*number as usize].insert(*letter);
+        }
+    }
+
+    screen.push_str("Number  Letters used\n");
+    for (i, letters) in number_letters.iter().enumerate().skip(1) {
+        if number_letter_counts[i] == 0 && letters.is_empty() {
+            continue;
+        }
+        let used = format!("{}/{}", letters.len(), number_letter_counts[i]);
+        let used = if letters.len() > number_letter_counts[i] as usize {
+            used.bright_red().to_string()
+        } else if letters.len() == number_letter_counts[i] as usize {
+            used.bright_green().to_string()
+        } else {
+            used
+        };
+        let letters: String = letters.iter().map(|c| format!("{} ", c)).collect();
+        screen.push_str(&format!(
+            "  {}    {:<5} {}\n",
+            format!("{:<2}", i).color(COLOURS[i % COLOURS.len()]),
+            used,
+            letters.color(COLOURS[i % COLOURS.len()])
+        ));
+    }
+
+    // Letters that are used anywhere in the solution, and the ones still available
+    let used = number_letters
+        .iter()
+        .fold(LetterSet::new(), |used, letters| used.union(*letters));
+    let remaining = alphabet.difference(used);
+    screen.push_str(&format!(
+        "\nUsed:      {}\n",
+        used.to_string().bright_green()
+    ));
+    screen.push_str(&format!("Remaining: {}\n\n", remaining));
+    screen.push_str(&format!("{}\n", status));
+
+    print!("{}", screen);
+}
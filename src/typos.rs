@@ -0,0 +1,455 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::solver;
+
+// A digit that was typed wrong, positions are characters in the ciphertext
+#[derive(Clone, Debug, PartialEq)]
+pub enum Edit {
+    // The digit at the position should have been another digit
+    Substituted {
+        position: usize,
+        typed: char,
+        correct: char,
+    },
+    // The digit at the position should not be there
+    Extra {
+        position: usize,
+        typed: char,
+    },
+    // A digit is missing before the position
+    Missing {
+        position: usize,
+        correct: char,
+    },
+}
+
+impl std::fmt::Display for Edit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Edit::Substituted {
+                position,
+                typed,
+                correct,
+            } => write!(
+                f,
+                "Position {}: {} should be {}",
+                position + 1,
+                typed,
+                correct
+            ),
+            Edit::Extra { position, typed } => {
+                write!(
+                    f,
+                    "Position {}: {} should not be there",
+                    position + 1,
+                    typed
+                )
+            }
+            Edit::Missing { position, correct } => {
+                write!(
+                    f,
+                    "Before position {}: {} is missing",
+                    position + 1,
+                    correct
+                )
+            }
+        }
+    }
+}
+
+// The ciphertext after fixing the typos, and the typos that were fixed
+pub struct Correction {
+    pub ciphertext: String,
+    pub edits: Vec<Edit>,
+}
+
+const DIGITS: [char; 9] = ['1', '2', '3', '4', '5', '6', '7', '8', '9'];
+
+impl Edit {
+    fn position(&self) -> usize {
+        match self {
+            Edit::Substituted { position, .. }
+            | Edit::Extra { position, .. }
+            | Edit::Missing { position, .. } => *position,
+        }
+    }
+}
+
+// Every number must appear a multiple of itself times (a letter under 3 appears 3 times), anything else can't be a ciphertext
+// This is how far a count is from that: how many of the number have to be added or removed at least
+fn count_distance(number: u32, count: usize) -> usize {
+    match number as usize {
+        0 => count,
+        number => (count % number).min(number - count % number),
+    }
+}
+
+// The numbers between brackets, or every digit on its own if there is no valid number between them (like the parser does)
+fn bracket_numbers(digits: &str) -> Vec<u32> {
+    match digits.parse::<u32>() {
+        Ok(number) => vec![number],
+        Err(_) => digits.chars().filter_map(|c| c.to_digit(10)).collect(),
+    }
+}
+
+// The text from start to end after the edits (the digits missing at the end are added too)
+fn apply(characters: &[char], edits: &[Edit], start: usize, end: usize) -> String {
+    let mut text = String::new();
+    let mut edits = edits
+        .iter()
+        .filter(|edit| (start..=end).contains(&edit.position()))
+        .peekable();
+    for position in start..=end {
+        let mut typed = characters.get(position).copied().filter(|_| position < end);
+        // A missing digit comes before the digit at its position, so it is always the first edit there
+        while let Some(edit) = edits.next_if(|edit| edit.position() == position) {
+            match edit {
+                Edit::Missing { correct, .. } => text.push(*correct),
+                Edit::Substituted { correct, .. } => typed = Some(*correct),
+                Edit::Extra { .. } => typed = None,
+            }
+        }
+        text.extend(typed);
+    }
+    text
+}
+
+// A ciphertext that is partly edited: the edits made up to the position, the rest is still as typed
+// The counts of the numbers in the whole ciphertext (edited part and the rest) are kept up to date with every edit,
+// so a branch can be dropped as soon as the edits that are left can't make every count divide anymore
+#[derive(Clone)]
+struct Partial {
+    position: usize,
+    edits_left: usize,
+    edits: Vec<Edit>,
+    number_counts: HashMap<u32, usize>,
+    // The sum of the distances of all counts, an edit adds a number, removes one or changes one into another,
+    // so it brings this down by at most 2
+    distance: usize,
+    // Which edits of a single digit keep the distance within reach, by the digit that is removed and the digit
+    // that is added (10 for none), the counts only change with the next edit so this is worked out once
+    reachable: [[bool; 11]; 11],
+}
+
+impl Partial {
+    fn new(
+        position: usize,
+        edits_left: usize,
+        edits: Vec<Edit>,
+        number_counts: HashMap<u32, usize>,
+        distance: usize,
+    ) -> Partial {
+        let mut partial = Partial {
+            position,
+            edits_left,
+            edits,
+            number_counts,
+            distance,
+            reachable: [[false; 11]; 11],
+        };
+        if edits_left > 0 {
+            // How the distance changes when a digit is removed or added
+            let mut removing = [None; 11];
+            let mut adding = [0; 11];
+            for digit in 0..10 {
+                let count = partial.count(digit as u32);
+                let before = count_distance(digit as u32, count) as isize;
+                // A digit that is not in the ciphertext can't be removed
+                if count > 0 {
+                    removing[digit] =
+                        Some(count_distance(digit as u32, count - 1) as isize - before);
+                }
+                adding[digit] = count_distance(digit as u32, count + 1) as isize - before;
+            }
+            removing[10] = Some(0);
+            let limit = 2 * (edits_left as isize - 1);
+            for (removed, reachable) in partial.reachable.iter_mut().enumerate() {
+                for (added, adding) in adding.iter().enumerate() {
+                    reachable[added] = removed != added
+                        && removing[removed].is_some_and(|change| {
+                            partial.distance as isize + change + adding <= limit
+                        });
+                }
+            }
+        }
+        partial
+    }
+
+    fn count(&self, number: u32) -> usize {
+        self.number_counts.get(&number).copied().unwrap_or(0)
+    }
+
+    // The distance after adding and removing some numbers
+    fn distance_after(&self, changes: &[(u32, isize)]) -> usize {
+        let mut total = self.distance;
+        for (number, change) in changes.iter() {
+            let count = self.count(*number);
+            total -= count_distance(*number, count);
+            total += count_distance(*number, count.checked_add_signed(*change).unwrap());
+        }
+        total
+    }
+}
+
+// The ciphertexts with up to a number of edits that could be a ciphertext at all, the ones with the fewest edits first
+// They are made one at a time, so the search can stop at the first one that can be solved
+pub struct Variants {
+    characters: Vec<char>,
+    // Where the brackets around the numbers of 10 or more are, and which positions are between them
+    // (a missing digit can also be added right before the closing bracket)
+    brackets: Vec<(usize, usize)>,
+    between_brackets: Vec<bool>,
+    missing_between_brackets: Vec<bool>,
+    max_edits: usize,
+    edit_count: usize,
+    start: Partial,
+    stack: Vec<Partial>,
+    // Fewer edits can end up with the same ciphertext (adding a digit and removing it again), those are only given once
+    seen: HashSet<String>,
+}
+
+impl Variants {
+    // The numbers an edit takes away and adds, an edit between brackets changes the whole number
+    fn changes(&self, edits: &[Edit], edit: &Edit) -> (Vec<u32>, Vec<u32>) {
+        let position = edit.position();
+        let missing = matches!(edit, Edit::Missing { .. });
+        let bracket = self.brackets.iter().find(|(open, close)| {
+            *open < position && (position < *close || (missing && position == *close))
+        });
+        if let Some((open, close)) = bracket {
+            let before = apply(&self.characters, edits, open + 1, *close);
+            let mut edits = edits.to_vec();
+            edits.push(edit.clone());
+            let after = apply(&self.characters, &edits, open + 1, *close);
+            return (bracket_numbers(&before), bracket_numbers(&after));
+        }
+
+        let digit = |c: &char| c.to_digit(10).unwrap();
+        match edit {
+            Edit::Substituted { typed, correct, .. } => (vec![digit(typed)], vec![digit(correct)]),
+            Edit::Extra { typed, .. } => (vec![digit(typed)], Vec::new()),
+            Edit::Missing { correct, .. } => (Vec::new(), vec![digit(correct)]),
+        }
+    }
+
+    // Make the edit, unless the edits that are left can't make every count divide anymore
+    fn edited(&self, partial: &Partial, edit: Edit) -> Option<Partial> {
+        let (removed, added) = self.changes(&partial.edits, &edit);
+        let mut changes: Vec<(u32, isize)> = Vec::new();
+        for (number, change) in removed
+            .iter()
+            .map(|number| (*number, -1))
+            .chain(added.iter().map(|number| (*number, 1)))
+        {
+            match changes.iter_mut().find(|(other, _)| *other == number) {
+                Some((_, total_change)) => *total_change += change,
+                None => changes.push((number, change)),
+            }
+        }
+
+        let total = partial.distance_after(&changes);
+        if total > 2 * (partial.edits_left - 1) {
+            return None;
+        }
+        // Without edits left the counts are not needed anymore
+        let mut number_counts = HashMap::new();
+        if partial.edits_left > 1 {
+            number_counts = partial.number_counts.clone();
+            for (number, change) in changes {
+                number_counts.insert(
+                    number,
+                    partial.count(number).checked_add_signed(change).unwrap(),
+                );
+            }
+        }
+
+        let position = match edit {
+            Edit::Missing { .. } => partial.position,
+            _ => partial.position + 1,
+        };
+        let mut edits = partial.edits.clone();
+        edits.push(edit);
+        Some(Partial::new(
+            position,
+            partial.edits_left - 1,
+            edits,
+            number_counts,
+            total,
+        ))
+    }
+
+    // Try an edit, edits of a single digit are looked up first as most of them are dropped
+    fn try_edit(&self, partial: &Partial, edit: Edit) -> Option<Partial> {
+        let position = edit.position();
+        let digit = |c: &char| c.to_digit(10).unwrap() as usize;
+        let (removed, added, between_brackets) = match &edit {
+            Edit::Substituted { typed, correct, .. } => (
+                digit(typed),
+                digit(correct),
+                self.between_brackets[position],
+            ),
+            Edit::Extra { typed, .. } => (digit(typed), 10, self.between_brackets[position]),
+            Edit::Missing { correct, .. } => {
+                (10, digit(correct), self.missing_between_brackets[position])
+            }
+        };
+        if !between_brackets && !partial.reachable[removed][added] {
+            return None;
+        }
+        self.edited(partial, edit)
+    }
+
+    // At the position keep, change or remove the digit, or add one before it
+    // The stack is last in first out, so the options are pushed the other way around
+    fn expand(&mut self, mut partial: Partial) {
+        let position = partial.position;
+        let mut options: Vec<Partial> = Vec::new();
+        if partial.edits_left > 0 {
+            // A digit can be missing before any digit, or at the end of a word
+            let after_digit = position > 0 && self.characters[position - 1].is_ascii_digit();
+            let before_digit =
+                position < self.characters.len() && self.characters[position].is_ascii_digit();
+            if after_digit || before_digit {
+                options.extend(DIGITS.iter().filter_map(|digit| {
+                    self.try_edit(
+                        &partial,
+                        Edit::Missing {
+                            position,
+                            correct: *digit,
+                        },
+                    )
+                }));
+            }
+        }
+        let Some(typed) = self.characters.get(position).copied() else {
+            self.stack.extend(options.into_iter().rev());
+            return;
+        };
+
+        let mut after: Vec<Partial> = Vec::new();
+        if typed.is_ascii_digit() && partial.edits_left > 0 {
+            after.extend(
+                DIGITS
+                    .iter()
+                    .filter(|digit| **digit != typed)
+                    .filter_map(|digit| {
+                        self.try_edit(
+                            &partial,
+                            Edit::Substituted {
+                                position,
+                                typed,
+                                correct: *digit,
+                            },
+                        )
+                    }),
+            );
+            after.extend(self.try_edit(&partial, Edit::Extra { position, typed }));
+        }
+
+        // Keep the character (spaces and punctuation are always kept, word boundaries are not guessed)
+        self.stack.extend(after.into_iter().rev());
+        partial.position += 1;
+        self.stack.push(partial);
+        self.stack.extend(options.into_iter().rev());
+    }
+}
+
+impl Iterator for Variants {
+    type Item = (String, Vec<Edit>);
+
+    fn next(&mut self) -> Option<(String, Vec<Edit>)> {
+        loop {
+            let Some(partial) = self.stack.pop() else {
+                // Every variant with this many edits has been given, go on with one more edit
+                if self.edit_count == self.max_edits {
+                    return None;
+                }
+                self.edit_count += 1;
+                let start = Partial::new(
+                    0,
+                    self.edit_count,
+                    Vec::new(),
+                    self.start.number_counts.clone(),
+                    self.start.distance,
+                );
+                self.stack.push(start);
+                continue;
+            };
+            if partial.edits_left > 0 {
+                self.expand(partial);
+                continue;
+            }
+
+            // Without edits left the rest stays as typed, so every count has to divide already
+            // (a bracketed number that appears a multiple of itself times is never too large to be a number either)
+            if partial.distance > 0 {
+                continue;
+            }
+            let variant = apply(&self.characters, &partial.edits, 0, self.characters.len());
+            if self.seen.insert(variant.clone()) {
+                return Some((variant, partial.edits));
+            }
+        }
+    }
+}
+
+// Every ciphertext that is at most this many edits away from the input, that could be a ciphertext at all
+// A number between brackets stays a number, also when it is too large for the fixed ciphertext (the parser would read
+// its digits one by one then, but that is not what was typed)
+pub fn variants(input: &str, max_edits: usize) -> Variants {
+    let characters: Vec<char> = input.chars().collect();
+
+    // A bracket that closes digits, the parser reads those as one number
+    let mut brackets = Vec::new();
+    for (open, _) in characters.iter().enumerate().filter(|(_, c)| **c == '[') {
+        let digits = characters[open + 1..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        if digits > 0 && characters.get(open + 1 + digits) == Some(&']') {
+            brackets.push((open, open + 1 + digits));
+        }
+    }
+
+    let mut between_brackets = vec![false; characters.len() + 1];
+    let mut missing_between_brackets = vec![false; characters.len() + 1];
+    for (open, close) in brackets.iter() {
+        between_brackets[open + 1..*close].fill(true);
+        missing_between_brackets[open + 1..=*close].fill(true);
+    }
+
+    let mut number_counts: HashMap<u32, usize> = HashMap::new();
+    for number in solver::parse_input_without_limit(input)
+        .into_iter()
+        .flatten()
+    {
+        *number_counts.entry(number).or_default() += 1;
+    }
+    let total = number_counts
+        .iter()
+        .map(|(number, count)| count_distance(*number, *count))
+        .sum();
+    let start = Partial::new(0, 0, Vec::new(), number_counts, total);
+    Variants {
+        characters,
+        brackets,
+        between_brackets,
+        missing_between_brackets,
+        max_edits,
+        edit_count: 0,
+        stack: vec![start.clone()],
+        start,
+        seen: HashSet::new(),
+    }
+}
+
+// Find the ciphertext with the fewest typos (at most max_edits) that can be solved
+pub fn correct(
+    input: &str,
+    max_edits: usize,
+    solvable: &mut dyn FnMut(&str) -> bool,
+) -> Option<Correction> {
+    variants(input, max_edits)
+        .find(|(ciphertext, _)| solvable(ciphertext))
+        .map(|(ciphertext, edits)| Correction { ciphertext, edits })
+}
@@ -0,0 +1,92 @@
+use color_eyre::owo_colors::OwoColorize;
+
+use crate::solver;
+
+pub enum Uniqueness {
+    // Exactly one decoding exists
+    Unique(Vec<Vec<char>>),
+    // At least two decodings exist, these are the first two found
+    Ambiguous(Vec<Vec<char>>, Vec<Vec<char>>),
+    // No decoding exists, with the first cipher word that has no candidates at all (if there is one)
+    Unsolvable(Option<usize>),
+}
+
+// Search for every decoding of the input, stopping as soon as a second one is found
+pub fn check_unique(input: &str, wordlist: &str) -> Uniqueness {
+    let wordlist = solver::filter_wordlist(wordlist);
+    let wordlist_tree = solver::construct_tree(wordlist.clone());
+    let number_letter_counts = solver::get_number_of_characters_per_number_count(input);
+    let input_numbers = solver::parse_input(input);
+    let mut solution = solver::empty_solution(&input_numbers);
+    let mut counts = solver::LetterCounts::new(&input_numbers, &number_letter_counts);
+
+    // A cipher word without any candidates makes the puzzle unsolvable, no need to search
+    if let Some(word) = (0..input_numbers.len()).find(|i| {
+        solver::count_candidates(&solution, &input_numbers, &mut counts, &wordlist, *i) == 0
+    }) {
+        return Uniqueness::Unsolvable(Some(word));
+    }
+
+    let mut solutions: Vec<Vec<Vec<char>>> = Vec::new();
+    solver::backtrack_recursive(
+        &mut solution,
+        &input_numbers,
+        &wordlist_tree,
+        &number_letter_counts,
+        &solver::Options::default(),
+        0,
+        0,
+        &mut |_| {},
+        &mut |solution| {
+            solutions.push(solution.to_vec());
+            solutions.len() == 2
+        },
+    );
+
+    let mut solutions = solutions.into_iter();
+    match (solutions.next(), solutions.next()) {
+        (Some(first), Some(second)) => Uniqueness::Ambiguous(first, second),
+        (Some(first), None) => Uniqueness::Unique(first),
+        _ => Uniqueness::Unsolvable(None),
+    }
+}
+
+fn format_solution(solution: &[Vec<char>]) -> String {
+    solution
+        .iter()
+        .map(|word| word.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub fn run(input: &str, wordlist: &str) {
+    println!("Ciphertext: {}", input.trim());
+    match check_unique(input, wordlist) {
+        Uniqueness::Unique(solution) => {
+            println!(
+                "{}: {}",
+                "Unique".bright_green(),
+                format_solution(&solution)
+            );
+        }
+        Uniqueness::Ambiguous(first, second) => {
+            println!("{}", "Ambiguous".bright_yellow());
+            println!("  {}", format_solution(&first));
+            println!("  {}", format_solution(&second));
+        }
+        Uniqueness::Unsolvable(Some(word)) => {
+            println!(
+                "{}: word {} ({}) has no candidates",
+                "Unsolvable".bright_red(),
+                word + 1,
+                solver::format_numbers(&solver::parse_input(input)[word])
+            );
+        }
+        Uniqueness::Unsolvable(None) => {
+            println!(
+                "{}: every word has candidates, but they don't fit together",
+                "Unsolvable".bright_red()
+            );
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use aivd_solver::batch;
+use aivd_solver::solver;
+use aivd_solver::strategy::{self, Dictionary};
+use std::path::PathBuf;
+
+const WORDLIST: &str = "de\nkat\nzit\nop\nmat\nhond\nloopt\nin\ntuin\n";
+
+// Every test gets its own empty directory
+fn batch_directory(name: &str) -> PathBuf {
+    let directory =
+        std::env::temp_dir().join(format!("aivd-batch-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[test]
+fn batch_file_has_a_puzzle_per_line() {
+    let directory = batch_directory("file");
+    let path = directory.join("puzzles.txt");
+    std::fs::write(
+        &path,
+        "# Two puzzles and one without a solution\n32 1333 13312 23 32 2123\n\n11\n9\n",
+    )
+    .unwrap();
+
+    let puzzles = batch::load_puzzles(&path).unwrap();
+    let names: Vec<&str> = puzzles.iter().map(|puzzle| puzzle.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["puzzles.txt:2", "puzzles.txt:4", "puzzles.txt:5"]
+    );
+
+    let dictionary = Dictionary::new(WORDLIST);
+    let backtracking = strategy::find_strategy("solver").unwrap();
+    let results = batch::solve_all(
+        &puzzles,
+        backtracking.as_ref(),
+        &dictionary,
+        &solver::Options::default(),
+        false,
+    );
+    assert_eq!(
+        results[0].solution.as_deref(),
+        Some("de hond loopt in de tuin")
+    );
+    assert_eq!(results[0].words, 6);
+    assert!(results[1].solution.is_some());
+    assert!(results[2].solution.is_none());
+    batch::print_summary(&results);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn batch_directory_has_a_puzzle_per_file() {
+    let directory = batch_directory("directory");
+    std::fs::write(directory.join("b.txt"), "22 123 113\n11 22 123\n").unwrap();
+    std::fs::write(directory.join("a.txt"), "32 1333 13312 23 32 2123").unwrap();
+
+    let puzzles = batch::load_puzzles(&directory).unwrap();
+    let names: Vec<&str> = puzzles.iter().map(|puzzle| puzzle.name.as_str()).collect();
+    assert_eq!(names, vec!["a.txt", "b.txt"]);
+
+    // The line break in b.txt is kept in the decoding
+    let dictionary = Dictionary::new(WORDLIST);
+    let backtracking = strategy::find_strategy("solver").unwrap();
+    let results = batch::solve_all(
+        &puzzles,
+        backtracking.as_ref(),
+        &dictionary,
+        &solver::Options::default(),
+        false,
+    );
+    assert_eq!(
+        results[1].solution.as_deref(),
+        Some("de kat zit\nop de mat")
+    );
+
+    // Without spaces the numbers of every puzzle are split into words again
+    std::fs::write(directory.join("b.txt"), "2212311311\n22123\n").unwrap();
+    let puzzles = batch::load_puzzles(&directory).unwrap();
+    let results = batch::solve_all(
+        &puzzles,
+        backtracking.as_ref(),
+        &dictionary,
+        &solver::Options::default(),
+        true,
+    );
+    assert_eq!(results[1].solution.as_deref(), Some("de kat zit op de mat"));
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,75 @@
+use aivd_solver::cache;
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+const WORDLIST: &str = "de\nkat\nzit\nop\nmat\nkerst-boom\nkerst\nkerstboom\nAnna\n";
+
+// Every test gets its own empty cache directory
+fn cache_directory(name: &str) -> PathBuf {
+    let directory =
+        std::env::temp_dir().join(format!("aivd-cache-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    directory
+}
+
+#[test]
+fn cached_dictionary_matches_the_built_one() {
+    let directory = cache_directory("roundtrip");
+    let built = cache::load_or_build(WORDLIST, &directory);
+    assert!(cache::cache_path(&directory, WORDLIST).exists());
+
+    let loaded = cache::load_or_build(WORDLIST, &directory);
+    assert_eq!(loaded.words, built.words);
+    // Only the words that had to be lowercased are copied, the others point into the wordlist
+    for (loaded_word, built_word) in loaded.words.iter().zip(built.words.iter()) {
+        assert_eq!(
+            matches!(loaded_word, Cow::Borrowed(_)),
+            matches!(built_word, Cow::Borrowed(_)),
+        );
+    }
+    for word in [
+        "de",
+        "anna",
+        "kat",
+        "kerst",
+        "kerstboom",
+        "kerst-boom",
+        "ker",
+        "boom",
+    ] {
+        assert_eq!(
+            loaded.tree.contains(word),
+            built.tree.contains(word),
+            "{}",
+            word
+        );
+        assert_eq!(
+            loaded.tree.word_signature(word),
+            built.tree.word_signature(word),
+            "{}",
+            word
+        );
+    }
+
+    // A different wordlist gets a different cache file
+    assert_ne!(
+        cache::cache_path(&directory, WORDLIST),
+        cache::cache_path(&directory, "de\nkat\n")
+    );
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn broken_cache_file_is_rebuilt() {
+    let directory = cache_directory("broken");
+    cache::load_or_build(WORDLIST, &directory);
+    let path = cache::cache_path(&directory, WORDLIST);
+
+    // Cut the file in half, it should be ignored and written again
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+    let dictionary = cache::load_or_build(WORDLIST, &directory);
+    assert!(dictionary.tree.contains("kerstboom"));
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
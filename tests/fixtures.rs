@@ -0,0 +1,957 @@
+use aivd_solver::letters::{self, LetterSet};
+use aivd_solver::strategy::{self, Dictionary, Puzzle, Source};
+use aivd_solver::{analyze, cnf, encoder, estimate, sat, solver, typos, uniqueness};
+
+// A small puzzle with its own wordlist and known answer, read from tests/fixtures
+struct Fixture {
+    name: String,
+    ciphertext: String,
+    answer: String,
+    wordlist: String,
+}
+
+// Fixture files have a header (comments, ciphertext and answer), a blank line, and then the wordlist
+fn load_fixtures() -> Vec<Fixture> {
+    let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+    let mut fixtures: Vec<Fixture> = std::fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            let contents = std::fs::read_to_string(&path).unwrap();
+            let (header, wordlist) = contents.split_once("\n\n").unwrap();
+            let field = |name: &str| {
+                header
+                    .lines()
+                    .find_map(|line| line.strip_prefix(name))
+                    .unwrap()
+                    .trim()
+                    .to_string()
+            };
+            Fixture {
+                name: path.file_stem().unwrap().to_string_lossy().to_string(),
+                ciphertext: field("ciphertext:"),
+                answer: field("answer:"),
+                wordlist: wordlist.to_string(),
+            }
+        })
+        .collect();
+    fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+    assert!(!fixtures.is_empty());
+    fixtures
+}
+
+// The fixture with this name
+fn fixture(name: &str) -> Fixture {
+    load_fixtures()
+        .into_iter()
+        .find(|fixture| fixture.name == name)
+        .unwrap()
+}
+
+// Solve a fixture with a strategy, and check that it decodes to its answer
+fn solve_fixture(fixture: &Fixture, strategy: &str, options: &solver::Options) {
+    let puzzle = Puzzle::parse(&fixture.ciphertext);
+    let dictionary = Dictionary::new(&fixture.wordlist);
+    let solution = strategy::find_strategy(strategy)
+        .unwrap()
+        .solve(&puzzle, &dictionary, options)
+        .solution
+        .unwrap_or_else(|| panic!("{} has no solution", fixture.name));
+    assert_eq!(to_sentence(&solution), fixture.answer, "{}", fixture.name);
+}
+
+fn to_sentence(solution: &[Vec<char>]) -> String {
+    solution
+        .iter()
+        .map(|word| word.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[test]
+fn fixtures_encode_to_their_ciphertext() {
+    for fixture in load_fixtures() {
+        let encoding = encoder::encode(&fixture.answer);
+        assert_eq!(encoding.ciphertext, fixture.ciphertext, "{}", fixture.name);
+    }
+}
+
+#[test]
+fn fixtures_decode_to_their_answer() {
+    for fixture in load_fixtures() {
+        let solution = solver::decode(&fixture.ciphertext, &fixture.wordlist)
+            .unwrap_or_else(|| panic!("{} has no solution", fixture.name));
+        assert_eq!(to_sentence(&solution), fixture.answer, "{}", fixture.name);
+    }
+}
+
+#[test]
+fn fixtures_are_unique() {
+    for fixture in load_fixtures() {
+        match uniqueness::check_unique(&fixture.ciphertext, &fixture.wordlist) {
+            uniqueness::Uniqueness::Unique(solution) => {
+                assert_eq!(to_sentence(&solution), fixture.answer, "{}", fixture.name)
+            }
+            _ => panic!("{} is not unique", fixture.name),
+        }
+    }
+}
+
+#[test]
+fn every_strategy_runs_on_the_fixtures() {
+    for fixture in load_fixtures() {
+        let puzzle = Puzzle::parse(&fixture.ciphertext);
+        let dictionary = Dictionary::new(&fixture.wordlist);
+        for strategy in strategy::strategies() {
+            let outcome = strategy.solve(&puzzle, &dictionary, &solver::Options::default());
+            if let Some(solution) = outcome.solution {
+                assert_eq!(
+                    to_sentence(&solution),
+                    fixture.answer,
+                    "{}",
+                    strategy.name()
+                );
+            }
+            if let Some(candidate_counts) = outcome.candidate_counts {
+                assert_eq!(candidate_counts.len(), puzzle.input_numbers.len());
+            }
+        }
+    }
+
+    // The backtracking strategy is the one that finds full decodings
+    assert!(strategy::find_strategy("solver").is_some());
+    assert!(strategy::find_strategy("unknown").is_none());
+}
+
+#[test]
+fn higher_priority_wordlists_win_the_ranking() {
+    let core = "op\n";
+    let theme = "in\nop\n";
+    let merged = [core, theme].join("\n");
+    let puzzle = Puzzle::parse("11");
+    let backtracking = strategy::find_strategy("solver").unwrap();
+
+    for (theme_priority, expected) in [(5, "in"), (-5, "op")] {
+        let mut dictionary = Dictionary::new(&merged);
+        dictionary.tag_sources(&[
+            Source {
+                name: "core".to_string(),
+                priority: 0,
+                wordlist: core,
+            },
+            Source {
+                name: "theme".to_string(),
+                priority: theme_priority,
+                wordlist: theme,
+            },
+        ]);
+        let solution = backtracking
+            .solve(&puzzle, &dictionary, &solver::Options::default())
+            .solution
+            .unwrap();
+        assert_eq!(to_sentence(&solution), expected);
+        assert_eq!(dictionary.sources_of("op"), vec!["core", "theme"]);
+        assert_eq!(dictionary.sources_of("in"), vec!["theme"]);
+    }
+
+    // Wordlists that end with a line break don't add an empty word when they are merged
+    let core = "bo\nom\nmoob\n";
+    let theme = "boom\n";
+    let merged = [core, theme].join("\n");
+    let mut dictionary = Dictionary::new(&merged);
+    dictionary.tag_sources(&[
+        Source {
+            name: "core".to_string(),
+            priority: 0,
+            wordlist: core,
+        },
+        Source {
+            name: "theme".to_string(),
+            priority: 5,
+            wordlist: theme,
+        },
+    ]);
+    assert!(!dictionary.words.iter().any(|word| word.is_empty()));
+    let options = solver::Options {
+        max_components: 2,
+        ..Default::default()
+    };
+    let outcome = backtracking.solve(&Puzzle::parse("1221"), &dictionary, &options);
+    assert_eq!(outcome.words, [["boom"]]);
+    assert_eq!(dictionary.score(&outcome.words), 5);
+    assert!(!outcome.truncated);
+
+    // Every pair of different letters decodes 11, far more solutions than are ranked
+    let pairs: Vec<String> = ('a'..='z')
+        .flat_map(|first| ('a'..='z').map(move |second| format!("{}{}", first, second)))
+        .filter(|pair| pair[..1] != pair[1..])
+        .collect();
+    let pairs = pairs.join("\n");
+    let mut dictionary = Dictionary::new(&pairs);
+    dictionary.tag_sources(&[
+        Source {
+            name: "core".to_string(),
+            priority: 0,
+            wordlist: &pairs,
+        },
+        Source {
+            name: "theme".to_string(),
+            priority: 1,
+            wordlist: "",
+        },
+    ]);
+    let outcome = backtracking.solve(
+        &Puzzle::parse("11"),
+        &dictionary,
+        &solver::Options::default(),
+    );
+    assert!(outcome.solution.is_some());
+    assert!(outcome.truncated);
+}
+
+#[test]
+fn check_unique_reports_ambiguous_and_unsolvable() {
+    // kat and mat have the same letter counts, so they can be swapped
+    let wordlist = "de\nkat\nzit\nop\nmat\n";
+    assert!(matches!(
+        uniqueness::check_unique("22 123 113 11 22 123", wordlist),
+        uniqueness::Uniqueness::Ambiguous(_, _)
+    ));
+
+    // There is no word with four letters in the wordlist
+    assert!(matches!(
+        uniqueness::check_unique("11 1111", wordlist),
+        uniqueness::Uniqueness::Unsolvable(Some(1))
+    ));
+}
+
+#[test]
+fn construct_tree_contains_only_the_words() {
+    let tree = solver::construct_tree(vec!["kerst", "kerstboom", "kat"]);
+    assert!(tree.contains("kerst"));
+    assert!(tree.contains("kerstboom"));
+    assert!(tree.contains("kat"));
+
+    // Prefixes and other words are not in the tree
+    assert!(!tree.contains("ker"));
+    assert!(!tree.contains("kerstbo"));
+    assert!(!tree.contains("mat"));
+    assert!(!tree.contains("katten"));
+}
+
+#[test]
+fn number_letter_counts_divide_by_the_number() {
+    // 1 appears 6 times, 2 appears 4 times and 3 twice
+    let counts = solver::get_number_of_characters_per_number_count("122 11 11 122 33");
+    assert_eq!(counts, [0, 6, 2, 0, 0, 0, 0, 0, 0, 0]);
+
+    let counts = solver::get_number_of_characters_per_number_count("32 1333 13312 23 32 2123");
+    assert_eq!(counts, [0, 4, 3, 3, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn candidates_follow_the_filled_in_letters() {
+    let wordlist = solver::filter_wordlist("de\nkat\nzit\nop\nmat\nkerst-boom\n");
+    let puzzle = Puzzle::parse("22 123 113 11 22 123");
+    let input_numbers = &puzzle.input_numbers;
+    let counts = |solution: &[Vec<char>]| {
+        solver::LetterCounts::from_solution(solution, input_numbers, &puzzle.number_letter_counts).0
+    };
+    let mut solution = solver::empty_solution(input_numbers);
+    assert_eq!(
+        solver::count_candidates(
+            &solution,
+            input_numbers,
+            &mut counts(&solution),
+            &wordlist,
+            2
+        ),
+        3
+    );
+
+    // Once kat puts a under number 2, kat and mat don't fit 113 anymore
+    solution[1] = vec!['k', 'a', 't'];
+    let mut letter_counts = counts(&solution);
+    assert_eq!(
+        solver::count_candidates(&solution, input_numbers, &mut letter_counts, &wordlist, 2),
+        1
+    );
+
+    // A second kat would make k (under 1) appear twice, which the letter counts rule out
+    assert_eq!(
+        solver::count_candidates(&solution, input_numbers, &mut letter_counts, &wordlist, 5),
+        2
+    );
+
+    // Filled in letters must match
+    solution[5][0] = 'm';
+    assert_eq!(
+        solver::count_candidates(
+            &solution,
+            input_numbers,
+            &mut counts(&solution),
+            &wordlist,
+            5
+        ),
+        1
+    );
+
+    // A pattern that only matches a word whose letters break the counts is not in the wordlist either:
+    // acc fits ..c, but a is already under 1 once
+    let puzzle = Puzzle::parse("11 122");
+    let dictionary = Dictionary::new("ab\nacc\ncdd\n");
+    let solution = vec![vec!['a', 'b'], vec![' ', ' ', 'c']];
+    assert!(matches!(
+        solver::find_conflict(
+            &solution,
+            &puzzle.input_numbers,
+            &dictionary.tree,
+            &puzzle.number_letter_counts,
+            &solver::Options::default()
+        ),
+        Some(solver::Conflict::NotInWordlist { word: 1, .. })
+    ));
+}
+
+#[test]
+fn compound_words_are_split_into_wordlist_words() {
+    let dictionary = Dictionary::new("kerst\nboom\nbo\nom\n");
+    let puzzle = Puzzle::parse("111111221");
+    let backtracking = strategy::find_strategy("solver").unwrap();
+
+    // Without compounds kerstboom is not in the wordlist
+    let options = solver::Options::default();
+    assert!(backtracking
+        .solve(&puzzle, &dictionary, &options)
+        .solution
+        .is_none());
+
+    let options = solver::Options {
+        max_components: 2,
+        ..Default::default()
+    };
+    let outcome = backtracking.solve(&puzzle, &dictionary, &options);
+    assert_eq!(to_sentence(&outcome.solution.unwrap()), "kerstboom");
+    assert_eq!(outcome.words, [["kerst", "boom"]]);
+
+    // Words are split into as few parts as possible, so a word from the wordlist stays whole
+    let options = solver::Options {
+        max_components: 3,
+        ..Default::default()
+    };
+    let solution: Vec<Vec<char>> = ["kerstboom", "boom", "kerstbom"]
+        .iter()
+        .map(|word| word.chars().collect())
+        .collect();
+    assert_eq!(
+        solver::split_words(&solution, &dictionary.tree),
+        [vec!["kerst", "boom"], vec!["boom"], vec![]]
+    );
+
+    // A single word beats a compound of two words
+    let dictionary = Dictionary::new("al\nom\nmest\n");
+    let solution = backtracking
+        .solve(&Puzzle::parse("1111"), &dictionary, &options)
+        .solution
+        .unwrap();
+    assert_eq!(to_sentence(&solution), "mest");
+}
+
+#[test]
+fn unknown_words_only_follow_the_numbers() {
+    // kerstfeest is missing from the wordlist
+    let dictionary = Dictionary::new("vrolijk\n");
+    let puzzle = Puzzle::parse("1211112 2322213322");
+    let backtracking = strategy::find_strategy("solver").unwrap();
+
+    let options = solver::Options::default();
+    assert!(backtracking
+        .solve(&puzzle, &dictionary, &options)
+        .solution
+        .is_none());
+
+    let options = solver::Options {
+        max_unknown_words: 1,
+        ..Default::default()
+    };
+    let solution = backtracking
+        .solve(&puzzle, &dictionary, &options)
+        .solution
+        .unwrap();
+    assert_eq!(solution[0].iter().collect::<String>(), "vrolijk");
+    assert_eq!(
+        solver::unknown_words(&solution, &dictionary.tree, &options),
+        vec![1]
+    );
+    assert!(solver::find_conflict(
+        &solution,
+        &puzzle.input_numbers,
+        &dictionary.tree,
+        &puzzle.number_letter_counts,
+        &options
+    )
+    .is_none());
+
+    // When every word is in the wordlist, no word is left unknown
+    let dictionary = Dictionary::new("vrolijk\nkerstfeest\n");
+    let solution = backtracking
+        .solve(&puzzle, &dictionary, &options)
+        .solution
+        .unwrap();
+    assert_eq!(to_sentence(&solution), "vrolijk kerstfeest");
+}
+
+#[test]
+fn ciphertext_without_spaces_is_split_into_words() {
+    for fixture in load_fixtures() {
+        let puzzle = Puzzle::parse_without_spaces(&fixture.ciphertext);
+        let dictionary = Dictionary::new(&fixture.wordlist);
+        let options = solver::Options {
+            max_components: puzzle.input_numbers[0].len(),
+            ..Default::default()
+        };
+        let outcome =
+            strategy::find_strategy("solver")
+                .unwrap()
+                .solve(&puzzle, &dictionary, &options);
+        let solution = outcome
+            .solution
+            .unwrap_or_else(|| panic!("{} has no solution", fixture.name));
+        assert_eq!(solution.len(), 1);
+        assert_eq!(
+            outcome.words[0].join(" "),
+            fixture.answer,
+            "{}",
+            fixture.name
+        );
+    }
+}
+
+#[test]
+fn typos_are_fixed_with_the_fewest_edits() {
+    let fixture = fixture("hond");
+    let dictionary = Dictionary::new(&fixture.wordlist);
+    let backtracking = strategy::find_strategy("solver").unwrap();
+    let mut solvable = |ciphertext: &str| {
+        backtracking
+            .solve(
+                &Puzzle::parse(ciphertext),
+                &dictionary,
+                &solver::Options::default(),
+            )
+            .solution
+            .is_some()
+    };
+
+    // A correct ciphertext needs no edits
+    let correction = typos::correct(&fixture.ciphertext, 1, &mut solvable).unwrap();
+    assert!(correction.edits.is_empty());
+
+    // One wrong, one extra and one missing digit
+    let typed = fixture.ciphertext.replacen("13312", "13322", 1);
+    let correction = typos::correct(&typed, 1, &mut solvable).unwrap();
+    assert_eq!(correction.ciphertext, fixture.ciphertext);
+    assert_eq!(
+        correction.edits,
+        vec![typos::Edit::Substituted {
+            position: 11,
+            typed: '2',
+            correct: '1'
+        }]
+    );
+
+    let typed = fixture.ciphertext.replacen("2123", "21233", 1);
+    let correction = typos::correct(&typed, 1, &mut solvable).unwrap();
+    assert_eq!(correction.ciphertext, fixture.ciphertext);
+
+    let typed = fixture.ciphertext.replacen("1333", "133", 1);
+    let correction = typos::correct(&typed, 2, &mut solvable).unwrap();
+    assert_eq!(correction.ciphertext, fixture.ciphertext);
+    assert_eq!(correction.edits.len(), 1);
+
+    // Too many typos to fix with a single edit
+    let typed = fixture.ciphertext.replacen("1333", "1", 1);
+    assert!(typos::correct(&typed, 1, &mut solvable).is_none());
+
+    // Only ciphertexts where every count divides are given, every one once and the ones with fewer edits first
+    let variants: Vec<(String, Vec<typos::Edit>)> = typos::variants("122 333", 2).collect();
+    assert_eq!(variants[0].0, "122 333");
+    assert!(variants
+        .windows(2)
+        .all(|pair| pair[0].1.len() <= pair[1].1.len()));
+    let mut ciphertexts: Vec<&str> = variants
+        .iter()
+        .map(|(variant, _)| variant.as_str())
+        .collect();
+    ciphertexts.sort_unstable();
+    ciphertexts.dedup();
+    assert_eq!(ciphertexts.len(), variants.len());
+    for ciphertext in ciphertexts {
+        let numbers: Vec<u32> = solver::parse_input(ciphertext).concat();
+        assert!(numbers.iter().all(|number| numbers
+            .iter()
+            .filter(|other| *other == number)
+            .count()
+            % *number as usize
+            == 0));
+    }
+    // A digit between brackets changes the whole number
+    assert!(typos::variants("2[12]1", 1).any(|(variant, _)| variant == "2[2]1"));
+}
+
+#[test]
+fn punctuation_and_line_breaks_pass_through() {
+    let fixture = fixture("hond");
+    let input = "\"32 1333, 13312\n23 32 2123!\" (AIVD)";
+    let puzzle = Puzzle::parse(input);
+    assert_eq!(
+        puzzle.input_numbers,
+        solver::parse_input(&fixture.ciphertext)
+    );
+    assert_eq!(
+        puzzle.number_letter_counts,
+        solver::get_number_of_characters_per_number_count(&fixture.ciphertext)
+    );
+
+    let solution = solver::decode(input, &fixture.wordlist).unwrap();
+    assert_eq!(
+        solver::render(input, &solution),
+        "\"de hond, loopt\nin de tuin!\" (AIVD)"
+    );
+
+    // Words that are not decoded yet keep their unknown letters as dots, and missing words keep their numbers
+    let mut partial = solver::empty_solution(&puzzle.input_numbers[..2]);
+    partial[0] = vec!['d', 'e'];
+    assert_eq!(
+        solver::render(input, &partial),
+        "\"de ...., 13312\n23 32 2123!\" (AIVD)"
+    );
+}
+
+#[test]
+fn numbers_of_ten_or_more_are_written_between_brackets() {
+    assert_eq!(
+        solver::parse_input("2[12]1 [3], 4567890123"),
+        vec![vec![2, 12, 1], vec![3], vec![4, 5, 6, 7, 8, 9, 0, 1, 2, 3]]
+    );
+    // Brackets without a number are punctuation
+    assert_eq!(solver::parse_input("[12 3]"), vec![vec![1, 2], vec![3]]);
+    // Numbers larger than the number of numbers in the input are malformed, no number can appear that often
+    // They are left out of the words instead of being read as digits, and reported
+    assert_eq!(solver::parse_input("[4000000000] 1[3]"), vec![vec![1, 3]]);
+    assert_eq!(
+        solver::oversized_numbers("[4000000000] 1[3] 2[99999999999]2"),
+        vec!["[4000000000]", "[99999999999]"]
+    );
+    assert_eq!(solver::parse_input("2[5]2"), vec![vec![2], vec![2]]);
+    assert!(solver::oversized_numbers("2[3]1").is_empty());
+    assert!(analyze::analyze("[4000000000] 1", &Dictionary::new("a\n"))
+        .inconsistencies
+        .iter()
+        .any(|inconsistency| inconsistency.starts_with("[4000000000] is larger")));
+    assert_eq!(solver::format_numbers(&[2, 12, 1]), "2[12]1");
+
+    // e appears 11 times
+    let plaintext = "een eend en een ree eet";
+    let encoding = encoder::encode(plaintext);
+    assert_eq!(
+        encoding.ciphertext,
+        "[11][11]4 [11][11]41 [11]4 [11][11]4 1[11][11] [11][11]1"
+    );
+    assert_eq!(encoding.number_letter_counts[11], 1);
+    assert_eq!(encoding.number_letter_counts[4], 1);
+    assert_eq!(encoding.number_letter_counts[1], 3);
+
+    let solution = solver::decode(&encoding.ciphertext, "een\neend\nen\nree\neet\n").unwrap();
+    assert_eq!(
+        encoder::encode(&to_sentence(&solution)).ciphertext,
+        encoding.ciphertext
+    );
+    assert_eq!(
+        solver::render(&encoding.ciphertext, &solution),
+        to_sentence(&solution)
+    );
+}
+
+#[test]
+fn long_ciphertexts_do_not_overflow_the_counts() {
+    // 300 times the same sentence, so t appears 900 times and the ciphertext has thousands of characters
+    let plaintext = vec!["de kat zit op de mat"; 300].join(" ");
+    let encoding = encoder::encode(&plaintext);
+    assert!(encoding.ciphertext.len() > 5000);
+
+    let puzzle = Puzzle::parse(&encoding.ciphertext);
+    assert_eq!(puzzle.input_numbers.len(), 1800);
+    assert_eq!(puzzle.input_numbers[1], vec![300, 600, 900]);
+    assert_eq!(puzzle.number_letter_counts, encoding.number_letter_counts);
+    assert_eq!(puzzle.number_letter_counts[300], 6);
+    assert_eq!(puzzle.number_letter_counts[600], 3);
+    assert_eq!(puzzle.number_letter_counts[900], 1);
+
+    // The plaintext itself fits every constraint
+    let dictionary = Dictionary::new("de\nkat\nzit\nop\nmat\n");
+    let solution: Vec<Vec<char>> = plaintext
+        .split_whitespace()
+        .map(|word| word.chars().collect())
+        .collect();
+    assert!(solver::find_conflict(
+        &solution,
+        &puzzle.input_numbers,
+        &dictionary.tree,
+        &puzzle.number_letter_counts,
+        &solver::Options::default()
+    )
+    .is_none());
+
+    // One t too many is noticed
+    let mut solution = solution;
+    solution[1][0] = 't';
+    assert!(solver::find_conflict(
+        &solution,
+        &puzzle.input_numbers,
+        &dictionary.tree,
+        &puzzle.number_letter_counts,
+        &solver::Options::default()
+    )
+    .is_some());
+}
+
+#[test]
+fn analyze_reports_budgets_candidates_and_inconsistencies() {
+    let fixture = fixture("hond");
+    let dictionary = Dictionary::new(&fixture.wordlist);
+    let report = analyze::analyze(&fixture.ciphertext, &dictionary);
+    assert_eq!(report.number_counts[1..4], [4, 6, 9]);
+    assert_eq!(report.number_letter_counts[1..4], [4, 3, 3]);
+    assert!(report.inconsistencies.is_empty());
+    for (before, after) in report
+        .candidates_before
+        .iter()
+        .zip(report.candidates_after.iter())
+    {
+        assert!(after <= before && *after > 0);
+    }
+    assert_eq!(
+        report.search_space,
+        report
+            .candidates_after
+            .iter()
+            .map(|candidates| *candidates as f64)
+            .product::<f64>()
+    );
+    analyze::print_report(&fixture.ciphertext, &report);
+
+    // 3 only appears once, and the wordlist has no words with 1 or 4 letters
+    let report = analyze::analyze("3 1111", &Dictionary::new("de\nkat\n"));
+    assert_eq!(report.inconsistencies.len(), 3);
+    assert_eq!(report.candidates_before, vec![0, 0]);
+
+    // Three different letters are needed, but the wordlist only has two
+    let report = analyze::analyze("1 1 1", &Dictionary::new("a\nb\n"));
+    assert!(report
+        .inconsistencies
+        .iter()
+        .any(|inconsistency| inconsistency.ends_with("the wordlist only has 2")));
+}
+
+#[test]
+fn estimate_bounds_the_search() {
+    // Two letters fit the single position, so the tree is the root and two leaves on every path
+    let puzzle = Puzzle::parse("1");
+    let dictionary = Dictionary::new("a\nb\n");
+    let estimate = estimate::estimate(&puzzle, &dictionary, &solver::Options::default(), 10);
+    assert_eq!(estimate.nodes, 3.0);
+    assert_eq!(estimate.word_combinations, Some(2.0));
+    // Only the two letters of the wordlist can be picked
+    assert_eq!(estimate.letter_assignments, 2.0);
+
+    let fixture = fixture("hond");
+    let puzzle = Puzzle::parse(&fixture.ciphertext);
+    let dictionary = Dictionary::new(&fixture.wordlist);
+    let estimate = estimate::estimate(&puzzle, &dictionary, &solver::Options::default(), 50);
+    assert!(estimate.nodes >= 1.0);
+    assert!(estimate
+        .word_combinations
+        .is_some_and(|combinations| combinations >= 1.0));
+
+    // Compounds and unknown words aren't among the candidates, so they can't be bounded by them
+    let options = solver::Options {
+        max_components: 2,
+        ..Default::default()
+    };
+    let estimate = estimate::estimate(&puzzle, &dictionary, &options, 10);
+    assert_eq!(estimate.word_combinations, None);
+
+    // 10 different letters: 4 under 1, 3 under 2 and 3 under 3
+    let orderings: f64 = 24.0 * 6.0 * 6.0;
+    let alphabet = dictionary.tree.letters().len();
+    let picks: f64 = (alphabet - 9..=alphabet).map(|i| i as f64).product();
+    assert!((estimate.letter_assignments - picks / orderings).abs() < 1.0);
+}
+
+#[test]
+fn most_constrained_order_finds_the_same_decodings() {
+    let options = solver::Options {
+        order: solver::Order::MostConstrained,
+        ..Default::default()
+    };
+    for fixture in load_fixtures() {
+        solve_fixture(&fixture, "solver", &options);
+    }
+
+    // Letters filled in by the user are kept
+    let input_numbers = solver::parse_input("22 123 113 11 22 123");
+    let dictionary = Dictionary::new("de\nkat\nzit\nop\nmat\n");
+    let mut solution = solver::empty_solution(&input_numbers);
+    solution[1] = vec!['m', 'a', 't'];
+    let counts = solver::get_number_of_characters_per_number_count("22 123 113 11 22 123");
+    assert!(solver::backtrack_recursive(
+        &mut solution,
+        &input_numbers,
+        &dictionary.tree,
+        &counts,
+        &options,
+        0,
+        0,
+        &mut |_| {},
+        &mut |_| true,
+    ));
+    assert_eq!(to_sentence(&solution), "de mat zit op de kat");
+    assert_eq!(solver::Order::parse("fixed"), Some(solver::Order::Fixed));
+    assert_eq!(solver::Order::parse("random"), None);
+}
+
+#[test]
+fn letter_order_changes_the_search_but_not_the_decoding() {
+    assert_eq!(
+        solver::letter_order_from_wordlist(&["een", "de", "en"])[..4],
+        ['e', 'n', 'd', 'a']
+    );
+    let order = solver::letter_order("enat");
+    assert_eq!(order.len(), 26);
+    assert_eq!(order[..5], ['e', 'n', 'a', 't', 'b']);
+
+    // Only letters that continue the prefix in the tree are tried
+    let dictionary = Dictionary::new("de\nkat\nkip\nmat\n");
+    let options = solver::Options {
+        letter_order: solver::letter_order("tpmk"),
+        ..Default::default()
+    };
+    let solution = vec![vec!['k', ' ', ' ']];
+    assert_eq!(
+        solver::candidate_letters(&solution, &dictionary.tree, &options, 0, 0),
+        vec!['m', 'k', 'd']
+    );
+    assert_eq!(
+        solver::candidate_letters(&solution, &dictionary.tree, &options, 0, 1),
+        vec!['a', 'i']
+    );
+
+    for fixture in load_fixtures() {
+        let options = solver::Options {
+            letter_order: solver::letter_order_from_wordlist(&solver::filter_wordlist(
+                &fixture.wordlist,
+            )),
+            ..Default::default()
+        };
+        solve_fixture(&fixture, "solver", &options);
+    }
+}
+
+#[test]
+fn letter_sets_combine_and_count() {
+    let vowels: LetterSet = "aeiou".chars().collect();
+    let word: LetterSet = "kastéél".chars().collect();
+    assert_eq!(vowels.intersection(word).to_string(), "a");
+    assert_eq!(vowels.union(word).to_string(), "aeiklostué");
+    assert_eq!(word.len(), 6);
+    assert!(word.contains('é') && !word.contains('e'));
+    assert_eq!(
+        LetterSet::alphabet().difference(vowels).len(),
+        26 - vowels.len()
+    );
+
+    // Spaces and punctuation are not letters, and words with them are left out of the wordlist
+    // Words are lowercased when the wordlist is read, so uppercase letters don't need a bit of their own
+    assert_eq!(letters::index(' '), None);
+    assert_eq!(letters::index('Z'), None);
+    assert!(!LetterSet::new().insert('-'));
+    assert_eq!(
+        solver::filter_wordlist("café\nkerst-boom\nΩmega\nZee\nÉén\n"),
+        vec!["café", "zee", "één"]
+    );
+
+    // The checks on the solution use the sets, and still point at the word where a letter was first used
+    let puzzle = Puzzle::parse("12 2");
+    let dictionary = Dictionary::new("ab\nbb\nb\na\n");
+    let solution = vec![vec!['a', 'b'], vec!['a']];
+    assert_eq!(
+        solver::find_conflict(
+            &solution,
+            &puzzle.input_numbers,
+            &dictionary.tree,
+            &puzzle.number_letter_counts,
+            &solver::Options::default(),
+        )
+        .map(|conflict| conflict.to_string()),
+        Some(
+            solver::Conflict::Propagation {
+                letter: 'a',
+                number: 2,
+                word: 0,
+                other_number: 1,
+            }
+            .to_string()
+        )
+    );
+}
+
+#[test]
+fn names_and_accented_words_are_decoded() {
+    // Names are capitalized in the wordlist, and accented letters are tried even though the letter order is a-z
+    let backtracking = strategy::find_strategy("solver").unwrap();
+    for (ciphertext, wordlist, answer) in [
+        ("2332 23 1121", "Anna\nen\nPiet\n", "anna en piet"),
+        ("1111 11", "café\nde\n", "café de"),
+    ] {
+        let puzzle = Puzzle::parse(ciphertext);
+        let dictionary = Dictionary::new(wordlist);
+        let solution = backtracking
+            .solve(&puzzle, &dictionary, &solver::Options::default())
+            .solution
+            .unwrap_or_else(|| panic!("{} has no solution", ciphertext));
+        assert_eq!(to_sentence(&solution), answer);
+    }
+    let order = solver::letter_order_from_wordlist(&["café", "dé"]);
+    assert_eq!(order[..6], ['é', 'a', 'c', 'd', 'f', 'b']);
+    assert_eq!(order.len(), 27);
+}
+
+#[test]
+fn running_letter_counts_follow_the_frequency_rules() {
+    let puzzle = Puzzle::parse("122 3 33");
+    let mut counts = solver::LetterCounts::new(&puzzle.input_numbers, &puzzle.number_letter_counts);
+    assert_eq!(counts.add('a', 1, 0), None);
+    assert_eq!(counts.add('b', 2, 0), None);
+    assert_eq!(counts.add('b', 2, 0), None);
+
+    // b is under 2 and already appears twice, a is under 1 and not under 3, and 2 only has room for b
+    assert_eq!(
+        counts.add('b', 2, 1),
+        Some(solver::Conflict::LetterCountExceeded {
+            letter: 'b',
+            number: 2
+        })
+    );
+    assert_eq!(
+        counts.add('a', 3, 1),
+        Some(solver::Conflict::Propagation {
+            letter: 'a',
+            number: 3,
+            word: 0,
+            other_number: 1
+        })
+    );
+    assert!(matches!(
+        counts.add('c', 2, 1),
+        Some(solver::Conflict::BudgetExceeded { number: 2, .. })
+    ));
+
+    // Removing letters undoes them, and every letter must appear exactly as often as its number at the end
+    counts.remove('b');
+    assert_eq!(counts.add('c', 3, 1), None);
+    assert!(!counts.is_complete());
+    assert_eq!(counts.add('b', 2, 0), None);
+    assert_eq!(counts.add('c', 3, 2), None);
+    assert_eq!(counts.add('c', 3, 2), None);
+    assert!(counts.is_complete());
+    assert_eq!(counts.mismatch(), None);
+
+    // Every word in the tree has its letter counts, in the order of the bits
+    let dictionary = Dictionary::new("kat\nkaas\n");
+    assert_eq!(
+        dictionary.tree.word_signature("kaas"),
+        Some(&[[0, 2], [10, 1], [18, 1]][..])
+    );
+    assert_eq!(dictionary.tree.word_signature("ka"), None);
+
+    // Whole words: a second a is too many, and no letter can appear more often than 3 (the largest number)
+    let mut counts = solver::LetterCounts::new(&puzzle.input_numbers, &puzzle.number_letter_counts);
+    assert_eq!(counts.add('a', 1, 0), None);
+    let signature = |word: &str| {
+        let mut signature = Vec::new();
+        letters::push_signature(&mut signature, &word.chars().collect::<Vec<char>>());
+        signature
+    };
+    let nothing_filled_in = [0; letters::CAPACITY];
+    assert!(counts
+        .signature_conflict(&signature("zaz"), &nothing_filled_in)
+        .is_some());
+    assert!(counts
+        .signature_conflict(&signature("zzzz"), &nothing_filled_in)
+        .is_some());
+    assert_eq!(
+        counts.signature_conflict(&signature("zzz"), &nothing_filled_in),
+        None
+    );
+    // The a of a word that is already filled in is counted already
+    assert_eq!(
+        counts.signature_conflict(&signature("zaz"), &letters::letter_counts(&['a'])),
+        None
+    );
+}
+
+#[test]
+fn sat_strategy_decodes_the_fixtures() {
+    for fixture in load_fixtures() {
+        solve_fixture(&fixture, "sat", &solver::Options::default());
+    }
+
+    // 2 appears 3 times, which no letter can do
+    let satisfiability = strategy::find_strategy("sat").unwrap();
+    let dictionary = Dictionary::new("de\nkat\n");
+    assert!(satisfiability
+        .solve(
+            &Puzzle::parse("22 122"),
+            &dictionary,
+            &solver::Options::default()
+        )
+        .solution
+        .is_none());
+
+    // Every way to pick 2 of 4 literals, and nothing else, satisfies exactly 2 of them
+    for assignment in 0..16u32 {
+        let mut clauses = cnf::Cnf::default();
+        let literals: Vec<i32> = (0..4).map(|_| clauses.new_variable()).collect();
+        clauses.exactly(&literals, 2);
+        for (i, literal) in literals.iter().enumerate() {
+            let value = assignment & (1 << i) != 0;
+            clauses.add_clause(vec![if value { *literal } else { -literal }]);
+        }
+        assert_eq!(
+            sat::solve(&clauses).is_some(),
+            assignment.count_ones() == 2,
+            "{:04b}",
+            assignment
+        );
+    }
+
+    // The DIMACS file names the candidate variables and has a header with the sizes
+    let encoding = cnf::encode(&Puzzle::parse("11 22"), &dictionary);
+    let mut dimacs = Vec::new();
+    encoding.cnf.write_dimacs(&mut dimacs).unwrap();
+    let dimacs = String::from_utf8(dimacs).unwrap();
+    assert!(dimacs.contains("c 1 word 1 is de\n"));
+    assert!(dimacs.contains(&format!(
+        "p cnf {} {}\n",
+        encoding.cnf.variables,
+        encoding.cnf.clauses.len()
+    )));
+    assert_eq!(
+        dimacs
+            .lines()
+            .filter(|line| line.ends_with(" 0") || *line == "0")
+            .count(),
+        encoding.cnf.clauses.len()
+    );
+}
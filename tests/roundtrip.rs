@@ -0,0 +1,68 @@
+use aivd_solver::strategy::{self, Dictionary, Puzzle};
+use aivd_solver::{encoder, solver};
+use proptest::prelude::*;
+
+const WORDS: [&str; 24] = [
+    "de", "het", "een", "kat", "zit", "op", "mat", "hond", "loopt", "in", "tuin", "kerst", "boom",
+    "vrolijk", "en", "is", "rood", "groen", "huis", "zee", "wit", "aan", "ik", "jij",
+];
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    // Encode a random sentence from the wordlist, and check that whatever the solver finds encodes to the same ciphertext
+    #[test]
+    fn solver_recovers_a_valid_decoding(sentence in prop::collection::vec(prop::sample::select(&WORDS[..]), 1..5)) {
+        let encoding = encoder::encode(&sentence.join(" "));
+        let wordlist = WORDS.join("\n");
+
+        let solution = solver::decode(&encoding.ciphertext, &wordlist);
+        prop_assert!(solution.is_some(), "no solution for {}", encoding.ciphertext);
+
+        // Every decoded word is in the wordlist, and the letter counts give back the ciphertext
+        let decoded: Vec<String> = solution.unwrap().iter().map(|word| word.iter().collect()).collect();
+        for word in decoded.iter() {
+            prop_assert!(WORDS.contains(&word.as_str()), "{} is not in the wordlist", word);
+        }
+        let reencoded = encoder::encode(&decoded.join(" "));
+        prop_assert_eq!(reencoded.ciphertext, encoding.ciphertext);
+        prop_assert_eq!(reencoded.number_letter_counts, encoding.number_letter_counts);
+    }
+
+    // Same for the SAT strategy, which only sees the clauses and not the letters
+    #[test]
+    fn sat_strategy_recovers_a_valid_decoding(sentence in prop::collection::vec(prop::sample::select(&WORDS[..]), 1..8)) {
+        let encoding = encoder::encode(&sentence.join(" "));
+        let wordlist = WORDS.join("\n");
+        let dictionary = Dictionary::new(&wordlist);
+
+        let solution = strategy::find_strategy("sat")
+            .unwrap()
+            .solve(&Puzzle::parse(&encoding.ciphertext), &dictionary, &solver::Options::default())
+            .solution;
+        prop_assert!(solution.is_some(), "no solution for {}", encoding.ciphertext);
+
+        let decoded: Vec<String> = solution.unwrap().iter().map(|word| word.iter().collect()).collect();
+        let reencoded = encoder::encode(&decoded.join(" "));
+        prop_assert_eq!(reencoded.ciphertext, encoding.ciphertext);
+    }
+
+    // The letter budget of a ciphertext is the number of letters with each count
+    #[test]
+    fn number_letter_counts_match_the_encoding(sentence in prop::collection::vec(prop::sample::select(&WORDS[..]), 1..8)) {
+        let encoding = encoder::encode(&sentence.join(" "));
+        for number in 1..10 {
+            let letters = encoding.letter_counts.iter().filter(|(_, count)| *count == number).count();
+            prop_assert_eq!(encoding.number_letter_counts[number] as usize, letters);
+        }
+    }
+
+    // Every word in the tree can be found, and words that weren't added can't
+    #[test]
+    fn construct_tree_finds_exactly_its_words(words in prop::sample::subsequence(&WORDS[..], 0..WORDS.len())) {
+        let tree = solver::construct_tree(words.clone());
+        for word in WORDS.iter() {
+            prop_assert_eq!(tree.contains(word), words.contains(word), "{}", word);
+        }
+    }
+}